@@ -1,6 +1,14 @@
-use std::collections::HashMap;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::balance::BalanceMap;
+use crate::config::{Collection, Config};
+use crate::health::HealthMap;
+use crate::patterns::CompiledPattern;
+use crate::schedule::ScheduleRule;
+use crate::storage::{CorruptMap, Storage};
 
 pub fn default_font_family() -> String {
     "sans-serif".to_string()
@@ -25,12 +33,235 @@ pub struct Theme {
     pub font_family: String,
 }
 
+// Which side wins when the incoming request and the saved URL both set a query key
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryPrecedence {
+    #[default]
+    Incoming,
+    Saved,
+}
+
+fn default_redirect_status() -> u16 {
+    302
+}
+
+fn is_default_redirect_status(status: &u16) -> bool {
+    *status == default_redirect_status()
+}
+
+// Redirect status codes a shortcut may use
+pub const REDIRECT_STATUSES: [u16; 4] = [301, 302, 307, 308];
+
+// A saved shortcut. Stored as a plain URL string when every option is at its
+// default, so existing `{ "alias": "url" }` files load and save unchanged.
+// `remote = "Self"` gives us the derived (de)serializers as inherent
+// functions, which the hand-written impls below fall back to for the object form.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(remote = "Self")]
+pub struct Shortcut {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub query_precedence: QueryPrecedence,
+    #[serde(default = "default_redirect_status", skip_serializing_if = "is_default_redirect_status")]
+    pub redirect_status: u16, // One of REDIRECT_STATUSES
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_max_age: Option<u32>, // Seconds browsers may cache the redirect; None means never
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destinations: Option<Destinations>, // Anything beyond the single `url`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<NaiveDate>, // From this day on the link is gone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>, // Who to ask about the link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>, // Alias to use instead; visitors see a countdown before forwarding
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub claims_host: bool, // Also answer requests sent to a host named after the alias (http://jira/123)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>, // Why the link exists
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>, // Unknown for shortcuts saved before timestamps were kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// Other ways a shortcut can choose where to send someone. `url` stays the
+// primary destination in every mode.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Destinations {
+    // Open `url` plus each of these in its own tab, from a launch page
+    Tabs { also_open: Vec<String> },
+    // First matching time window wins; `url` when none match
+    Schedule { rules: Vec<ScheduleRule> },
+    // Roster that hands over to the next URL every `every_days` days
+    Rotation { starts: NaiveDate, every_days: u32, urls: Vec<String> },
+    // First route whose network holds the client wins; `url` for everyone else
+    Network { routes: Vec<NetworkRoute> },
+    // First of `url` and then these that passes its health check wins
+    Failover { alternatives: Vec<String> },
+    // Spreads hits across these in proportion to their weights; `url` if none has weight
+    Balance { urls: Vec<WeightedUrl> },
+}
+
+// A destination for clients inside one of the networks named in config.json
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NetworkRoute {
+    pub network: String,
+    pub url: String,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+// One destination of a balanced shortcut. Equal weights mean plain round-robin.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WeightedUrl {
+    pub url: String,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+impl Shortcut {
+    pub fn new(url: impl Into<String>) -> Self {
+        Shortcut {
+            url: url.into(),
+            query_precedence: QueryPrecedence::default(),
+            redirect_status: default_redirect_status(),
+            cache_max_age: None,
+            destinations: None,
+            expires: None,
+            owner: None,
+            deprecated: None,
+            claims_host: false,
+            description: None,
+            tags: Vec::new(),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.expires.is_some_and(|expires| today >= expires)
+    }
+
+    fn has_only_url(&self) -> bool {
+        self.query_precedence == QueryPrecedence::default()
+            && self.redirect_status == default_redirect_status()
+            && self.cache_max_age.is_none()
+            && self.destinations.is_none()
+            && self.expires.is_none()
+            && self.owner.is_none()
+            && self.deprecated.is_none()
+            && !self.claims_host
+            && self.description.is_none()
+            && self.tags.is_empty()
+            && self.created_at.is_none()
+            && self.updated_at.is_none()
+    }
+}
+
+impl Serialize for Shortcut {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.has_only_url() {
+            serializer.serialize_str(&self.url)
+        } else {
+            Shortcut::serialize(self, serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Shortcut {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // On-disk representation: either a bare URL or an object with options
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Url(String),
+            #[serde(with = "Shortcut")]
+            Full(Shortcut),
+        }
+
+        Ok(match Stored::deserialize(deserializer)? {
+            Stored::Url(url) => Shortcut::new(url),
+            Stored::Full(shortcut) => shortcut,
+        })
+    }
+}
+
 pub struct AppState {
-    pub shortcuts: Mutex<HashMap<String, String>>,
-    pub hidden_shortcuts: Mutex<HashMap<String, String>>,
-    pub work_shortcuts: Mutex<HashMap<String, String>>,
+    pub shortcuts: Mutex<HashMap<String, Shortcut>>,
+    pub hidden_shortcuts: Mutex<HashMap<String, Shortcut>>,
+    pub work_shortcuts: Mutex<HashMap<String, Shortcut>>,
+    pub pattern_shortcuts: Mutex<Vec<CompiledPattern>>, // Kept sorted by priority
+    pub health: Mutex<HealthMap>, // Failover destinations' last probe results
+    pub balance: Mutex<BalanceMap>, // Rotation position and recent picks of balanced shortcuts
+    pub claimed_hosts: Mutex<HashSet<String>>, // Normalized aliases with `claims_host` set; see hosts.rs
 
     // THEME STATE
     pub current_theme: Mutex<Theme>, // The theme currently applied
     pub saved_themes: Mutex<HashMap<String, Theme>>, // All available themes
+
+    // Where the collections and themes above are saved
+    pub storage: Box<dyn Storage>,
+    pub corrupt: Mutex<CorruptMap>, // Whatever failed to parse at load; read-only until fixed
+
+    // Read-only settings from config.json
+    pub config: Config,
+}
+
+impl AppState {
+    /// The in-memory shortcuts of `collection`.
+    pub fn collection(&self, collection: Collection) -> &Mutex<HashMap<String, Shortcut>> {
+        match collection {
+            Collection::Shortcuts => &self.shortcuts,
+            Collection::Hidden => &self.hidden_shortcuts,
+            Collection::Work => &self.work_shortcuts,
+        }
+    }
+}
+
+#[cfg(test)]
+impl AppState {
+    /// Empty state around `config`, saved nowhere, for handler tests.
+    pub fn for_tests(config: Config) -> AppState {
+        AppState {
+            shortcuts: Mutex::new(HashMap::new()),
+            hidden_shortcuts: Mutex::new(HashMap::new()),
+            work_shortcuts: Mutex::new(HashMap::new()),
+            pattern_shortcuts: Mutex::new(Vec::new()),
+            health: Mutex::new(HashMap::new()),
+            balance: Mutex::new(HashMap::new()),
+            claimed_hosts: Mutex::new(HashSet::new()),
+            current_theme: Mutex::new(crate::elements::theme::default_dark_theme()),
+            saved_themes: Mutex::new(HashMap::new()),
+            storage: Box::new(crate::storage::MemoryStorage::default()),
+            corrupt: Mutex::new(HashMap::new()),
+            config,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcut_expires_at_the_start_of_its_expiry_date() {
+        let mut shortcut = Shortcut::new("https://example.com");
+        let day: NaiveDate = "2026-03-01".parse().unwrap();
+        assert!(!shortcut.is_expired(day));
+
+        shortcut.expires = Some(day);
+        assert!(!shortcut.is_expired(day.pred_opt().unwrap()));
+        assert!(shortcut.is_expired(day));
+        assert!(shortcut.is_expired(day.succ_opt().unwrap()));
+    }
 }
@@ -0,0 +1,104 @@
+// Spreading a shortcut's hits across several destinations. Uses smooth
+// weighted round-robin, so a 3:1 split sends exactly three of every four
+// requests to the first URL, interleaved rather than in bursts.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::app_state::WeightedUrl;
+
+/// How many of the latest redirects the table's share figures cover.
+pub const RECENT_WINDOW: usize = 100;
+
+#[derive(Default)]
+pub struct BalanceState {
+    current: Vec<i64>,       // Running score per destination
+    recent: VecDeque<usize>, // Indexes of the latest picks, newest last
+}
+
+/// Per-destination-list state, keyed by `balance_key`, so aliases that
+/// chain to the same shortcut share one rotation.
+pub type BalanceMap = HashMap<String, BalanceState>;
+
+// Identifies a destination list; editing the URLs or weights starts afresh
+fn balance_key(urls: &[WeightedUrl]) -> String {
+    urls.iter()
+        .map(|u| format!("{} {}", u.weight, u.url))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Picks the next destination and records the pick. `None` when every weight is zero.
+pub fn next_url<'s>(balance: &Mutex<BalanceMap>, urls: &'s [WeightedUrl]) -> Option<&'s str> {
+    let total: i64 = urls.iter().map(|u| i64::from(u.weight)).sum();
+    if total == 0 {
+        return None;
+    }
+
+    let mut balance = balance.lock().unwrap();
+    let state = balance.entry(balance_key(urls)).or_default();
+    state.current.resize(urls.len(), 0);
+
+    for (score, url) in state.current.iter_mut().zip(urls) {
+        *score += i64::from(url.weight);
+    }
+    let picked = (0..urls.len()).max_by_key(|&i| (state.current[i], std::cmp::Reverse(i)))?;
+    state.current[picked] -= total;
+
+    state.recent.push_back(picked);
+    if state.recent.len() > RECENT_WINDOW {
+        state.recent.pop_front();
+    }
+    Some(&urls[picked].url)
+}
+
+/// How many of the recent redirects went to each destination, and how many
+/// recent redirects there were in all.
+pub fn recent_counts(balance: &Mutex<BalanceMap>, urls: &[WeightedUrl]) -> (Vec<usize>, usize) {
+    let mut counts = vec![0; urls.len()];
+    let balance = balance.lock().unwrap();
+    let Some(state) = balance.get(&balance_key(urls)) else {
+        return (counts, 0);
+    };
+    for &i in &state.recent {
+        if let Some(count) = counts.get_mut(i) {
+            *count += 1;
+        }
+    }
+    (counts, state.recent.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weighted(urls: &[(&str, u32)]) -> Vec<WeightedUrl> {
+        urls.iter().map(|(url, weight)| WeightedUrl { url: url.to_string(), weight: *weight }).collect()
+    }
+
+    #[test]
+    fn picks_follow_the_weights_interleaved() {
+        let balance = Mutex::new(BalanceMap::new());
+        let urls = weighted(&[("a", 3), ("b", 1)]);
+        let picks: Vec<&str> = (0..8).map(|_| next_url(&balance, &urls).unwrap()).collect();
+        assert_eq!(picks, ["a", "a", "b", "a", "a", "a", "b", "a"]);
+        assert_eq!(recent_counts(&balance, &urls), (vec![6, 2], 8));
+    }
+
+    #[test]
+    fn zero_weight_is_never_picked() {
+        let balance = Mutex::new(BalanceMap::new());
+        let urls = weighted(&[("a", 0), ("b", 1)]);
+        assert!((0..5).all(|_| next_url(&balance, &urls) == Some("b")));
+        assert_eq!(next_url(&balance, &weighted(&[("a", 0)])), None);
+    }
+
+    #[test]
+    fn changed_weights_start_a_new_rotation() {
+        let balance = Mutex::new(BalanceMap::new());
+        next_url(&balance, &weighted(&[("a", 1), ("b", 1)]));
+        let changed = weighted(&[("a", 1), ("b", 2)]);
+        assert_eq!(next_url(&balance, &changed), Some("b"));
+        assert_eq!(recent_counts(&balance, &changed), (vec![0, 1], 1));
+    }
+}
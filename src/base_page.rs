@@ -112,6 +112,7 @@ pub fn nav_bar_html() -> String {
     <div class="modern-nav">
       <div class="nav-left">
         <a href="/" class="nav-link-item">Home</a>
+        <a href="/_backups" class="nav-link-item">Backups</a>
       </div>
       <div class="nav-right">
         <div id="optional-button-placeholder"></div>
@@ -153,6 +154,7 @@ pub fn render_base_page(
     <meta charset="utf-8">
     <title>{}</title>
     <link rel="icon" type="image/x-icon" href="/static/favicon.ico">
+    <link rel="search" type="application/opensearchdescription+xml" title="go" href="/opensearch.xml">
     {}
     {}
     {}
@@ -185,14 +187,68 @@ pub fn render_add_shortcut_modal() -> String {
 <dialog id="addShortcutModal">
   <div class="modal-content">
     <span class="close-btn" id="closeModalBtn">&times;</span>
-    <h2>Add New Shortcut</h2>
+    <h2 id="shortcutModalTitle">Add New Shortcut</h2>
     <form action="/add_shortcut" method="POST" class="modal-form">
+      <label for="kind">Type:</label>
+      <select id="kind" name="kind">
+        <option value="alias" selected>Alias</option>
+        <option value="wildcard">Wildcard pattern (e.g., ticket-*)</option>
+        <option value="regex">Regex pattern (e.g., pr-(\d+))</option>
+      </select>
+
       <label for="shortcut">Shortcut:</label>
       <input type="text" id="shortcut" name="shortcut" placeholder="e.g., gh" required>
 
       <label for="url">URL:</label>
       <input type="url" id="url" name="url" placeholder="e.g., https://github.com" required>
 
+      <label for="description">Description (optional):</label>
+      <input type="text" id="description" name="description" placeholder="e.g., Team wiki home page">
+
+      <label for="tags">Tags (comma-separated, optional):</label>
+      <input type="text" id="tags" name="tags" placeholder="e.g., docs, team">
+
+      <label for="extra_urls">Also open in new tabs (one URL per line, optional):</label>
+      <textarea id="extra_urls" name="extra_urls" rows="3" placeholder="e.g., https://grafana.example.com"></textarea>
+
+      <input type="hidden" id="keep_destinations" name="keep_destinations" value="">
+      <input type="hidden" id="collection" name="collection" value="">
+      <p id="keepDestinationsNote" style="display: none;">This shortcut's schedule or other advanced destinations are kept as they are. Change them in the JSON file.</p>
+
+      <label for="priority">Pattern priority (lower is tried first):</label>
+      <input type="text" id="priority" name="priority" inputmode="numeric" placeholder="0">
+
+      <label for="query_precedence">When the request and URL share a query key:</label>
+      <select id="query_precedence" name="query_precedence">
+        <option value="incoming" selected>Request value wins</option>
+        <option value="saved">Saved value wins</option>
+      </select>
+
+      <label for="redirect_status">Redirect type:</label>
+      <select id="redirect_status" name="redirect_status">
+        <option value="302" selected>302 Found (temporary)</option>
+        <option value="307">307 Temporary Redirect</option>
+        <option value="301">301 Moved Permanently</option>
+        <option value="308">308 Permanent Redirect</option>
+      </select>
+
+      <label for="cache_max_age">Browser cache max-age (seconds, blank = never cache):</label>
+      <input type="text" id="cache_max_age" name="cache_max_age" inputmode="numeric" placeholder="e.g., 86400">
+
+      <label for="owner">Owner (optional):</label>
+      <input type="text" id="owner" name="owner" placeholder="e.g., platform team">
+
+      <label for="expires">Expires on (optional):</label>
+      <input type="date" id="expires" name="expires">
+
+      <label for="deprecated">Deprecated, use this alias instead (optional):</label>
+      <input type="text" id="deprecated" name="deprecated" placeholder="e.g., wiki">
+
+      <div style="margin-top: 15px;">
+        <input type="checkbox" id="claims_host" name="claims_host" value="true">
+        <label for="claims_host" style="display: inline; font-weight: normal;">Answer on its own host name (http://alias/...)?</label>
+      </div>
+
       <div style="margin-top: 15px;">
         <input type="checkbox" id="hidden" name="hidden" value="true">
         <label for="hidden" style="display: inline; font-weight: normal;">Hidden?</label>
@@ -215,10 +271,46 @@ pub fn render_add_shortcut_modal() -> String {
 
     if (btn && modal) {{
       btn.onclick = function() {{
+        modal.querySelector('form').reset();
+        document.getElementById("keep_destinations").value = "";
+        document.getElementById("keepDestinationsNote").style.display = "none";
+        document.getElementById("collection").value = "";
+        document.getElementById("hidden").disabled = false;
+        document.getElementById("shortcutModalTitle").textContent = "Add New Shortcut";
         modal.showModal();
       }}
     }}
 
+    // Edit buttons in the shortcuts table reopen the modal filled with that shortcut
+    document.querySelectorAll('.edit-button').forEach((editBtn) => {{
+      editBtn.onclick = function() {{
+        var form = modal.querySelector('form');
+        form.reset();
+        document.getElementById("shortcutModalTitle").textContent = "Edit Shortcut";
+        document.getElementById("kind").value = "alias";
+        document.getElementById("shortcut").value = editBtn.dataset.key;
+        document.getElementById("url").value = editBtn.dataset.url;
+        document.getElementById("query_precedence").value = editBtn.dataset.queryPrecedence;
+        document.getElementById("redirect_status").value = editBtn.dataset.redirectStatus;
+        document.getElementById("cache_max_age").value = editBtn.dataset.cacheMaxAge;
+        document.getElementById("extra_urls").value = editBtn.dataset.extraUrls;
+        document.getElementById("description").value = editBtn.dataset.description;
+        document.getElementById("tags").value = editBtn.dataset.tags;
+        document.getElementById("owner").value = editBtn.dataset.owner;
+        document.getElementById("expires").value = editBtn.dataset.expires;
+        document.getElementById("deprecated").value = editBtn.dataset.deprecated;
+        document.getElementById("claims_host").checked = editBtn.dataset.claimsHost === "true";
+        // The edit is saved back to the shortcut's own collection, so the checkbox only reports it
+        document.getElementById("collection").value = editBtn.dataset.collection;
+        document.getElementById("hidden").checked = editBtn.dataset.collection === "hidden";
+        document.getElementById("hidden").disabled = true;
+        var keep = editBtn.dataset.keepDestinations === "true";
+        document.getElementById("keep_destinations").value = keep ? "true" : "";
+        document.getElementById("keepDestinationsNote").style.display = keep ? "block" : "none";
+        modal.showModal();
+      }}
+    }});
+
     if (span && modal) {{
       span.onclick = function() {{
         modal.close();
@@ -0,0 +1,240 @@
+use serde::Deserialize;
+use std::{collections::HashMap, fs, io};
+
+// Service-wide settings read once from config.json at startup. Every field has
+// a default so the file is optional and only needs the keys being changed.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+    pub suggestions: SuggestionConfig,
+    pub normalization: NormalizationPolicy,
+    pub fallback: FallbackConfig,
+    pub timezone: Option<String>, // IANA zone for scheduled shortcuts; server local time if unset
+    pub networks: HashMap<String, Vec<String>>, // Named CIDR ranges, e.g. "lan": ["192.168.1.0/24"]
+    pub trust_forwarded_for: bool, // Take the client address from the last X-Forwarded-For entry (only behind a proxy you run)
+    pub health_check: HealthCheckConfig,
+    pub lifecycle: LifecycleConfig,
+    pub collection_order: Vec<Collection>, // Which file wins when a key is in several; unlisted ones follow in the default order
+    pub hosts: HashMap<String, Vec<Collection>>, // Host name -> the only collections it serves, in search order
+    pub storage: StorageConfig,
+}
+
+// The shortcut files, as named in `collection_order`
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Collection {
+    Shortcuts, // shortcuts.json
+    Hidden,    // hidden-shortcuts.json
+    Work,      // work-shortcuts.json
+}
+
+impl Collection {
+    pub const ALL: [Collection; 3] = [Collection::Shortcuts, Collection::Hidden, Collection::Work];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Collection::Shortcuts => "shortcuts",
+            Collection::Hidden => "hidden",
+            Collection::Work => "work",
+        }
+    }
+}
+
+impl Config {
+    /// Every collection once, in the order keys are looked up.
+    pub fn search_order(&self) -> Vec<Collection> {
+        let mut order: Vec<Collection> = Vec::new();
+        for collection in self.collection_order.iter().chain(&Collection::ALL) {
+            if !order.contains(collection) {
+                order.push(*collection);
+            }
+        }
+        order
+    }
+
+    /// The collections a request for `host` can reach, in search order. Hosts
+    /// not listed under `hosts` reach every collection, in `search_order`.
+    pub fn collections_for(&self, host: Option<&str>) -> Vec<Collection> {
+        let Some(listed) = host.and_then(|host| {
+            let name = host_name(host);
+            self.hosts.iter().find(|(h, _)| h.eq_ignore_ascii_case(name)).map(|(_, c)| c)
+        }) else {
+            return self.search_order();
+        };
+
+        let mut collections: Vec<Collection> = Vec::new();
+        for collection in listed {
+            if !collections.contains(collection) {
+                collections.push(*collection);
+            }
+        }
+        collections
+    }
+}
+
+/// A Host header without its port: "wgo:8080" -> "wgo", "[::1]:80" -> "[::1]"
+pub fn host_name(host: &str) -> &str {
+    match host.find(']') {
+        Some(end) if host.starts_with('[') => &host[..=end],
+        _ => host.split(':').next().unwrap_or(host),
+    }
+}
+
+// "Did you mean" suggestions on the not-found page
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct SuggestionConfig {
+    pub include_hidden: bool, // Also suggest aliases from hidden-shortcuts.json
+    pub limit: usize,         // Maximum number of suggestions shown
+}
+
+impl Default for SuggestionConfig {
+    fn default() -> Self {
+        SuggestionConfig {
+            include_hidden: false,
+            limit: 5,
+        }
+    }
+}
+
+// How aliases are canonicalized when saved and when looked up
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct NormalizationPolicy {
+    pub case_fold: bool,            // "GH" matches "gh"
+    pub fold_separators: bool,      // "git-hub" and "git_hub" match "github"
+    pub unicode_nfc: bool,          // Composed and decomposed accents match
+    pub strip_trailing_slash: bool, // "gh/" matches "gh"
+}
+
+impl Default for NormalizationPolicy {
+    fn default() -> Self {
+        NormalizationPolicy {
+            case_fold: true,
+            fold_separators: true,
+            unicode_nfc: true,
+            strip_trailing_slash: true,
+        }
+    }
+}
+
+// How often failover destinations are probed
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct HealthCheckConfig {
+    pub interval_secs: u64, // Time between rounds of probes
+    pub timeout_ms: u64,    // A probe slower than this counts as down
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        HealthCheckConfig {
+            interval_secs: 30,
+            timeout_ms: 2000,
+        }
+    }
+}
+
+// Expiring and deprecated shortcuts
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct LifecycleConfig {
+    pub expiring_soon_days: i64,        // How far ahead the home page lists upcoming expiries
+    pub deprecation_countdown_secs: u32, // How long the deprecation notice shows before forwarding
+}
+
+impl Default for LifecycleConfig {
+    fn default() -> Self {
+        LifecycleConfig {
+            expiring_soon_days: 14,
+            deprecation_countdown_secs: 5,
+        }
+    }
+}
+
+// Where shortcuts and themes are kept
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    #[default]
+    Json,   // One JSON file per collection, next to config.json
+    Memory, // Nothing is written; everything is lost on restart
+    Sqlite, // One database at `sqlite_path`; needs a build with the `sqlite` feature
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    pub backend: StorageBackend,
+    pub sqlite_path: String, // Created, and filled from the JSON files, on first start
+    pub backups: usize,      // Earlier versions of each JSON file to keep; 0 keeps none
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            backend: StorageBackend::default(),
+            sqlite_path: "go.sqlite3".to_string(),
+            backups: 5,
+        }
+    }
+}
+
+// What happens when nothing matches the requested alias
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackMode {
+    #[default]
+    Table,   // Show the not-found page with every shortcut
+    Search,  // Redirect to `search_url` with the request as the query
+    Suggest, // Redirect to the local fuzzy-search page
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct FallbackConfig {
+    pub mode: FallbackMode,
+    pub search_url: String, // `{q}` is replaced with the percent-encoded request
+}
+
+impl Default for FallbackConfig {
+    fn default() -> Self {
+        FallbackConfig {
+            mode: FallbackMode::default(),
+            search_url: "https://duckduckgo.com/?q={q}".to_string(),
+        }
+    }
+}
+
+pub fn load_config(path: &str) -> io::Result<Config> {
+    let data = fs::read_to_string(path)?;
+    let config: Config = serde_json::from_str(&data)?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(collections: &[Collection]) -> Vec<&'static str> {
+        collections.iter().map(|c| c.name()).collect()
+    }
+
+    #[test]
+    fn search_order_lists_every_collection_once() {
+        let config: Config = serde_json::from_str(r#"{"collection_order": ["work", "work"]}"#).unwrap();
+        assert_eq!(names(&config.search_order()), ["work", "shortcuts", "hidden"]);
+        assert_eq!(names(&Config::default().search_order()), ["shortcuts", "hidden", "work"]);
+    }
+
+    #[test]
+    fn collections_for_uses_the_host_entry_without_its_port() {
+        let config: Config = serde_json::from_str(
+            r#"{"collection_order": ["work"], "hosts": {"WGO": ["work", "hidden", "work"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(names(&config.collections_for(Some("wgo:8080"))), ["work", "hidden"]);
+        assert_eq!(names(&config.collections_for(Some("go"))), ["work", "shortcuts", "hidden"]);
+        assert_eq!(names(&config.collections_for(None)), ["work", "shortcuts", "hidden"]);
+    }
+}
@@ -0,0 +1,144 @@
+// Picks the one URL a shortcut sends this particular request to, for modes
+// that choose between several (schedules, rosters, client networks, failover).
+
+use actix_web::{http::header, HttpRequest};
+use chrono::NaiveDateTime;
+use ipnet::IpNet;
+use std::net::IpAddr;
+use std::sync::Mutex;
+
+use crate::app_state::{AppState, Destinations, Shortcut};
+use crate::balance::BalanceMap;
+use crate::config::Config;
+use crate::health::{failover_candidates, HealthMap};
+use crate::schedule::{now_in, rotation_url, scheduled_url};
+
+// Everything about the current request that can change where a shortcut goes
+pub struct PickContext<'a> {
+    pub now: NaiveDateTime,
+    pub client_ip: Option<IpAddr>,
+    pub config: &'a Config,
+    pub health: &'a Mutex<HealthMap>,
+    pub balance: &'a Mutex<BalanceMap>,
+}
+
+impl<'a> PickContext<'a> {
+    pub fn new(req: &HttpRequest, state: &'a AppState) -> Self {
+        PickContext {
+            now: now_in(state.config.timezone.as_deref()),
+            client_ip: client_ip(req, state.config.trust_forwarded_for),
+            config: &state.config,
+            health: &state.health,
+            balance: &state.balance,
+        }
+    }
+
+    /// Name of the first configured network containing the client, if any.
+    pub fn client_network(&self) -> Option<&'a str> {
+        let ip = self.client_ip?;
+        let mut names: Vec<&String> = self.config.networks.keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .find(|name| in_network(self.config, name, ip))
+            .map(String::as_str)
+    }
+}
+
+/// The address a request came from. The socket peer is used unless
+/// `trust_forwarded_for` is on, in which case the last address in
+/// `X-Forwarded-For` wins: that's the one the proxy in front of us added.
+/// Earlier entries came from the client and can say anything.
+pub fn client_ip(req: &HttpRequest, trust_forwarded_for: bool) -> Option<IpAddr> {
+    if trust_forwarded_for
+        && let Some(forwarded) = req.headers().get("X-Forwarded-For").and_then(|v| v.to_str().ok())
+        && let Some(ip) = forwarded.rsplit(',').next().and_then(|last| last.trim().parse().ok())
+    {
+        return Some(ip);
+    }
+    req.peer_addr().map(|addr| addr.ip())
+}
+
+/// The host name the request was addressed to, port included, if it gave one.
+pub fn request_host(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| req.uri().host())
+}
+
+/// Whether `ip` falls in any CIDR range configured under `network`.
+pub fn in_network(config: &Config, network: &str, ip: IpAddr) -> bool {
+    // Compare IPv4-mapped IPv6 peers (::ffff:a.b.c.d) against IPv4 ranges
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        v4 => v4,
+    };
+    config
+        .networks
+        .get(network)
+        .into_iter()
+        .flatten()
+        .filter_map(|cidr| cidr.parse::<IpNet>().ok())
+        .any(|net| net.contains(&ip))
+}
+
+/// The URL a single-destination shortcut sends this request to. Falls back to
+/// `url` when no scheduled window, roster entry, network route or healthy
+/// failover destination applies.
+pub fn pick_url<'s>(shortcut: &'s Shortcut, context: &PickContext) -> &'s str {
+    let picked = match &shortcut.destinations {
+        Some(Destinations::Schedule { rules }) => scheduled_url(rules, context.now),
+        Some(Destinations::Rotation { starts, every_days, urls }) => {
+            rotation_url(urls, *starts, *every_days, context.now)
+        }
+        Some(Destinations::Network { routes }) => context.client_ip.and_then(|ip| {
+            routes
+                .iter()
+                .find(|r| in_network(context.config, &r.network, ip))
+                .map(|r| r.url.as_str())
+        }),
+        Some(Destinations::Failover { .. }) => {
+            // Not probed yet counts as healthy, so nothing changes until a check fails
+            let health = context.health.lock().unwrap();
+            failover_candidates(shortcut)
+                .into_iter()
+                .flatten()
+                .find(|url| health.get(*url) != Some(&false))
+        }
+        _ => None,
+    };
+    picked.unwrap_or(&shortcut.url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        let mut config = Config::default();
+        config.networks.insert(
+            "office".to_string(),
+            vec!["10.1.0.0/16".to_string(), "fd00::/8".to_string(), "not a cidr".to_string()],
+        );
+        config
+    }
+
+    #[test]
+    fn addresses_inside_any_range_are_in_the_network() {
+        let config = config();
+        assert!(in_network(&config, "office", "10.1.2.3".parse().unwrap()));
+        assert!(in_network(&config, "office", "fd12::1".parse().unwrap()));
+        assert!(!in_network(&config, "office", "10.2.0.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn ipv4_mapped_peers_match_ipv4_ranges() {
+        assert!(in_network(&config(), "office", "::ffff:10.1.0.9".parse().unwrap()));
+    }
+
+    #[test]
+    fn unknown_network_holds_nobody() {
+        assert!(!in_network(&config(), "lab", "10.1.2.3".parse().unwrap()));
+    }
+}
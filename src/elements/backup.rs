@@ -0,0 +1,85 @@
+use actix_web::{
+    post,
+    web::{Data, Form},
+    HttpResponse, Responder,
+};
+use serde::Deserialize;
+use std::{io, sync::Arc};
+
+use crate::app_state::AppState;
+use crate::config::Collection;
+use crate::hosts::refresh_claimed_hosts;
+use crate::storage::{report_load_error, Stored};
+
+// Struct to capture the backup picked on the backups page
+#[derive(Deserialize)]
+pub struct RestoreBackupForm {
+    pub name: String,
+    pub generation: usize,
+}
+
+// Reads everything back from storage, since a restore replaced one of the
+// files. Whatever now loads (or is gone) is writable again; whatever doesn't
+// parse is marked read-only and keeps its in-memory contents.
+fn reload(state: &AppState) {
+    let mut results: Vec<(Stored, io::Result<()>)> = Vec::new();
+    for (collection, shortcuts) in [
+        (Collection::Shortcuts, &state.shortcuts),
+        (Collection::Hidden, &state.hidden_shortcuts),
+        (Collection::Work, &state.work_shortcuts),
+    ] {
+        results.push((
+            Stored::Collection(collection),
+            state.storage.load_shortcuts(collection).map(|loaded| *shortcuts.lock().unwrap() = loaded),
+        ));
+    }
+    results.push((
+        Stored::Patterns,
+        state.storage.load_patterns().map(|loaded| *state.pattern_shortcuts.lock().unwrap() = loaded),
+    ));
+    results.push((
+        Stored::Themes,
+        state.storage.load_themes().map(|loaded| *state.saved_themes.lock().unwrap() = loaded),
+    ));
+    results.push((
+        Stored::CurrentTheme,
+        state.storage.load_current_theme().map(|loaded| *state.current_theme.lock().unwrap() = loaded),
+    ));
+
+    refresh_claimed_hosts(state);
+
+    // Only taken once the collections are unlocked, as pages lock them first
+    let mut corrupt = state.corrupt.lock().unwrap();
+    for (item, result) in results {
+        match result {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => report_load_error(item, &e, &mut corrupt),
+            _ => {
+                corrupt.remove(&item);
+            }
+        }
+    }
+}
+
+// Handler for putting a backup back in place
+#[post("/restore_backup")]
+pub async fn restore_backup(
+    form: Form<RestoreBackupForm>,
+    state: Data<Arc<AppState>>,
+) -> impl Responder {
+    if let Err(e) = state.storage.restore_backup(&form.name, form.generation) {
+        eprintln!("Failed to restore {} backup {}: {}", form.name, form.generation, e);
+        let mut response = match e.kind() {
+            io::ErrorKind::NotFound | io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => {
+                HttpResponse::BadRequest()
+            }
+            _ => HttpResponse::InternalServerError(),
+        };
+        return response.body(format!("Failed to restore the backup: {}", e));
+    }
+
+    reload(&state);
+
+    HttpResponse::Found()
+        .append_header(("Location", "/_backups"))
+        .finish()
+}
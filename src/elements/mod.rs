@@ -1,2 +1,19 @@
 pub mod theme;
 pub mod shortcut;
+pub mod backup;
+
+use actix_web::HttpResponse;
+
+use crate::app_state::AppState;
+use crate::storage::Stored;
+
+// Turns away a save into something that failed to parse at load, so the
+// unreadable data isn't replaced with the empty stand-in
+pub fn refuse_if_corrupt(state: &AppState, item: Stored) -> Option<HttpResponse> {
+    let corrupt = state.corrupt.lock().unwrap();
+    let found = corrupt.get(&item)?;
+    Some(HttpResponse::Conflict().body(format!(
+        "Can't save {}: {}. It is read-only until the file is fixed and the service restarted, or a backup is restored.",
+        item, found
+    )))
+}
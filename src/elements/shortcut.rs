@@ -1,22 +1,27 @@
 use actix_web::{
     post,
     web::{Data, Form},
-    HttpResponse, Responder,
+    HttpRequest, HttpResponse, Responder,
 };
+use percent_encoding::utf8_percent_encode;
+use chrono::Utc;
 use serde::Deserialize;
 use std::{
     collections::HashMap,
-    fs,
-    io,
+    str::FromStr,
     sync::Arc,
 };
 
-use crate::app_state::AppState;
-
-// File constants
-static SHORTCUTS_FILE: &str = "shortcuts.json";
-static HIDDEN_SHORTCUTS_FILE: &str = "hidden-shortcuts.json";
-static WORK_SHORTCUTS_FILE: &str = "work-shortcuts.json";
+use crate::app_state::{AppState, Destinations, QueryPrecedence, Shortcut, REDIRECT_STATUSES};
+use crate::destinations::request_host;
+use crate::config::{Collection, NormalizationPolicy};
+use crate::lookup::{alias_target, arrange, follow_chain, lookup_indexed, ChainError};
+use crate::normalize::normalize_alias;
+use crate::resolve::QUERY_VALUE;
+use crate::storage::Stored;
+use crate::elements::refuse_if_corrupt;
+use crate::hosts::refresh_claimed_hosts;
+use crate::patterns::{compile, sort_by_priority, CompiledPattern, PatternKind, PatternShortcut};
 
 // Struct to capture the shortcut form data
 #[derive(Deserialize)]
@@ -24,60 +29,288 @@ pub struct AddShortcutForm {
     pub shortcut: String,
     pub url: String,
     pub hidden: Option<String>,
+    pub collection: Option<String>, // Set when editing: the collection the shortcut is saved in
+    pub query_precedence: Option<QueryPrecedence>,
+    pub kind: Option<String>,     // "alias" (default), "wildcard" or "regex"
+    pub priority: Option<String>, // Pattern priority; lower is tried first
+    pub redirect_status: Option<u16>,
+    pub cache_max_age: Option<String>, // Seconds; blank means never cache
+    pub extra_urls: Option<String>,    // More tabs to open, one URL per line
+    pub keep_destinations: Option<String>, // Set when editing a shortcut whose destinations the modal can't show
+    pub expires: Option<String>,           // YYYY-MM-DD; blank means never
+    pub owner: Option<String>,
+    pub deprecated: Option<String>, // Alias to use instead
+    pub claims_host: Option<String>, // Checkbox: answer on http://<alias>/ too
+    pub description: Option<String>,
+    pub tags: Option<String>, // Comma-separated
 }
 
 // Struct to capture the key for deletion
 #[derive(Deserialize)]
 pub struct DeleteShortcutForm {
     pub key: String,
+    pub collection: String, // Only this collection's copy goes; others with the same key stay
+}
+
+// Patterns are deleted separately, so an alias and a pattern spelled the same never take each other out
+#[derive(Deserialize)]
+pub struct DeletePatternForm {
+    pub pattern: String,
+}
+
+// Parses an optional number from a text input, treating blank as absent
+fn parse_optional<T: FromStr>(value: Option<&str>) -> Result<Option<T>, T::Err> {
+    match value.map(str::trim).filter(|v| !v.is_empty()) {
+        None => Ok(None),
+        Some(v) => v.parse().map(Some),
+    }
 }
 
-// Helper function to save shortcuts back to JSON file
-fn save_shortcuts(path: &str, shortcuts: &HashMap<String, String>) -> io::Result<()> {
-    let data = serde_json::to_string_pretty(shortcuts)?;
-    fs::write(path, data)
+// The collection a form names by `Collection::name`
+fn collection_named(name: &str) -> Result<Collection, HttpResponse> {
+    Collection::ALL
+        .into_iter()
+        .find(|c| c.name() == name)
+        .ok_or_else(|| HttpResponse::BadRequest().body(format!("No collection called {}.", name)))
+}
+
+// Refuses changes to a collection the request's host doesn't serve
+fn refuse_unless_served(served: &[Collection], collection: Collection) -> Option<HttpResponse> {
+    (!served.contains(&collection)).then(|| {
+        HttpResponse::BadRequest().body(format!("This host doesn't serve the {} collection.", collection.name()))
+    })
+}
+
+// Validates and stores a wildcard or regex shortcut
+fn add_pattern(form: &AddShortcutForm, kind: PatternKind, state: &AppState) -> HttpResponse {
+    if let Some(refusal) = refuse_if_corrupt(state, Stored::Patterns) {
+        return refusal;
+    }
+
+    let priority = match parse_optional(form.priority.as_deref()) {
+        Ok(priority) => priority.unwrap_or(0),
+        Err(_) => return HttpResponse::BadRequest().body("Priority must be a whole number."),
+    };
+
+    let pattern = PatternShortcut {
+        pattern: form.shortcut.trim().to_string(),
+        kind,
+        url: form.url.trim().to_string(),
+        priority,
+    };
+
+    let regex = match compile(&pattern) {
+        Ok(regex) => regex,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Invalid pattern: {}", e)),
+    };
+
+    let mut patterns = state.pattern_shortcuts.lock().unwrap();
+    patterns.retain(|p| p.shortcut.pattern != pattern.pattern);
+    patterns.push(CompiledPattern { shortcut: pattern, regex: Some(regex) });
+    sort_by_priority(&mut patterns);
+
+    if let Err(e) = state.storage.save_patterns(&patterns) {
+        eprintln!("Failed to save pattern shortcuts: {}", e);
+        return HttpResponse::InternalServerError().body("Failed to save pattern shortcut.");
+    }
+
+    HttpResponse::Found()
+        .append_header(("Location", "/"))
+        .finish()
 }
 
 // Handler for the new shortcut form
 #[post("/add_shortcut")]
 pub async fn add_shortcut(
+    req: HttpRequest,
     form: Form<AddShortcutForm>,
     state: Data<Arc<AppState>>,
 ) -> impl Responder {
     let is_hidden = form.hidden.is_some();
-    let shortcut = form.shortcut.trim();
+    let shortcut = normalize_alias(form.shortcut.trim(), &state.config.normalization);
     let url = form.url.trim();
 
     if shortcut.is_empty() || url.is_empty() {
         return HttpResponse::BadRequest().body("Shortcut and URL cannot be empty.");
     }
 
-    if is_hidden {
-        let mut hidden_shortcuts = state.hidden_shortcuts.lock().unwrap();
-        hidden_shortcuts.insert(shortcut.to_string(), url.to_string());
+    match form.kind.as_deref() {
+        Some("wildcard") => return add_pattern(&form, PatternKind::Wildcard, &state),
+        Some("regex") => return add_pattern(&form, PatternKind::Regex, &state),
+        _ => {}
+    }
+
+    // An edit goes back where the shortcut came from. A new one goes to the
+    // hidden collection if the checkbox says so, otherwise to the first one
+    // this host serves. Nothing is saved where this host can't see it.
+    let served = state.config.collections_for(request_host(&req));
+    let target = match form.collection.as_deref().filter(|c| !c.is_empty()) {
+        Some(name) => match collection_named(name) {
+            Ok(collection) => collection,
+            Err(refusal) => return refusal,
+        },
+        None if is_hidden => Collection::Hidden,
+        None => served.iter().copied().find(|c| *c != Collection::Hidden).unwrap_or(Collection::Hidden),
+    };
+    if let Some(refusal) = refuse_unless_served(&served, target) {
+        return refusal;
+    }
+    if let Some(refusal) = refuse_if_corrupt(&state, Stored::Collection(target)) {
+        return refusal;
+    }
+
+    let redirect_status = form.redirect_status.unwrap_or(302);
+    if !REDIRECT_STATUSES.contains(&redirect_status) {
+        return HttpResponse::BadRequest().body("Redirect status must be 301, 302, 307 or 308.");
+    }
+
+    let cache_max_age = match parse_optional(form.cache_max_age.as_deref()) {
+        Ok(max_age) => max_age,
+        Err(_) => return HttpResponse::BadRequest().body("Cache max-age must be a whole number of seconds."),
+    };
+
+    let expires = match parse_optional(form.expires.as_deref()) {
+        Ok(expires) => expires,
+        Err(_) => return HttpResponse::BadRequest().body("Expiry must be a date like 2026-12-31."),
+    };
+    // Blank text inputs mean "not set"
+    let optional_text = |value: &Option<String>| {
+        value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+    };
+
+    let also_open: Vec<String> = form
+        .extra_urls
+        .as_deref()
+        .unwrap_or("")
+        .lines()
+        .map(str::trim)
+        .filter(|u| !u.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let mut tags: Vec<String> = Vec::new();
+    for tag in form.tags.as_deref().unwrap_or("").split(',').map(str::trim) {
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
 
-        if let Err(e) = save_shortcuts(HIDDEN_SHORTCUTS_FILE, &hidden_shortcuts) {
-            eprintln!("Failed to save hidden shortcuts: {}", e);
-            return HttpResponse::InternalServerError().body("Failed to save hidden shortcut.");
+    // Saving over an existing key is an edit: it keeps the key as it was
+    // written, even if that isn't the normalized form, and its creation time
+    let policy = &state.config.normalization;
+    let (shortcut, existing) = {
+        let saved = state.collection(target).lock().unwrap();
+        match lookup_indexed(&[&*saved], form.shortcut.trim(), policy) {
+            Some((_, key, existing)) => (key.clone(), Some(existing.clone())),
+            None => (shortcut, None),
         }
-    } else {
-        let mut shortcuts = state.shortcuts.lock().unwrap();
-        shortcuts.insert(shortcut.to_string(), url.to_string());
+    };
+    let now = Utc::now();
 
-        if let Err(e) = save_shortcuts(SHORTCUTS_FILE, &shortcuts) {
-            eprintln!("Failed to save shortcuts: {}", e);
+    let mut destinations = (!also_open.is_empty()).then_some(Destinations::Tabs { also_open });
+    if destinations.is_none() && form.keep_destinations.as_deref().is_some_and(|k| !k.is_empty()) {
+        destinations = existing.as_ref().and_then(|s| s.destinations.clone());
+    }
+
+    let new_shortcut = Shortcut {
+        url: url.to_string(),
+        query_precedence: form.query_precedence.unwrap_or_default(),
+        redirect_status,
+        cache_max_age,
+        destinations,
+        expires,
+        owner: optional_text(&form.owner),
+        deprecated: optional_text(&form.deprecated),
+        claims_host: form.claims_host.is_some(),
+        description: optional_text(&form.description),
+        tags,
+        created_at: existing.map_or(Some(now), |s| s.created_at),
+        updated_at: Some(now),
+    };
+
+    // Reject a `go:` link that would loop back on itself once saved
+    if alias_target(url).is_some() {
+        let shortcuts = state.shortcuts.lock().unwrap();
+        let hidden_shortcuts = state.hidden_shortcuts.lock().unwrap();
+        let work_shortcuts = state.work_shortcuts.lock().unwrap();
+        let arranged = arrange(&served, &shortcuts, &hidden_shortcuts, &work_shortcuts);
+
+        let mut updated = match target {
+            Collection::Shortcuts => shortcuts.clone(),
+            Collection::Hidden => hidden_shortcuts.clone(),
+            Collection::Work => work_shortcuts.clone(),
+        };
+        updated.insert(shortcut.clone(), new_shortcut.clone());
+        let collections: Vec<&HashMap<String, Shortcut>> = arranged
+            .iter()
+            .map(|(c, map)| if *c == target { &updated } else { *map })
+            .collect();
+
+        if let Err(e @ (ChainError::Cycle(_) | ChainError::TooDeep(_))) =
+            follow_chain(&collections, &shortcut, &new_shortcut, policy)
+        {
+            return HttpResponse::BadRequest().body(e.to_string());
+        }
+    }
+
+    {
+        let mut saved = state.collection(target).lock().unwrap();
+        saved.insert(shortcut.clone(), new_shortcut);
+
+        if let Err(e) = state.storage.save_shortcuts(target, &saved) {
+            eprintln!("Failed to save {} shortcuts: {}", target.name(), e);
             return HttpResponse::InternalServerError().body("Failed to save shortcut.");
         }
     }
+    refresh_claimed_hosts(&state);
 
+    // The home page checks the saved key for shadowing and says so
+    let location = format!(
+        "/?saved={}:{}",
+        target.name(),
+        utf8_percent_encode(&shortcut, QUERY_VALUE)
+    );
     HttpResponse::Found()
-        .append_header(("Location", "/"))
+        .append_header(("Location", location))
         .finish()
 }
 
+/// Explains how a key saved into `target` interacts with the same alias in
+/// other collections, since only one of them can answer go/<key>. Nothing
+/// when no other collection has it, or `target` isn't served here.
+pub fn shadowing_warning(
+    arranged: &[(Collection, &HashMap<String, Shortcut>)],
+    target: Collection,
+    key: &str,
+    policy: &NormalizationPolicy,
+) -> Option<String> {
+    let target_position = arranged.iter().position(|(c, _)| *c == target)?;
+    let messages: Vec<String> = arranged
+        .iter()
+        .enumerate()
+        .filter(|(_, (c, _))| *c != target)
+        .filter_map(|(position, (other, map))| {
+            let (_, other_key, _) = lookup_indexed(&[*map], key, policy)?;
+            Some(if position < target_position {
+                format!(
+                    "go/{} still goes to the {} version (\"{}\"), which is looked up before {}.",
+                    key,
+                    other.name(),
+                    other_key,
+                    target.name()
+                )
+            } else {
+                format!("This shadows the {} version of go/{} (\"{}\").", other.name(), key, other_key)
+            })
+        })
+        .collect();
+    (!messages.is_empty()).then(|| messages.join(" "))
+}
+
 // Handler for deleting a shortcut
 #[post("/delete_shortcut")]
 pub async fn delete_shortcut(
+    req: HttpRequest,
     form: Form<DeleteShortcutForm>,
     state: Data<Arc<AppState>>,
 ) -> impl Responder {
@@ -86,32 +319,275 @@ pub async fn delete_shortcut(
         return HttpResponse::BadRequest().body("Shortcut key cannot be empty.");
     }
 
+    let collection = match collection_named(&form.collection) {
+        Ok(collection) => collection,
+        Err(refusal) => return refusal,
+    };
+    if let Some(refusal) = refuse_unless_served(&state.config.collections_for(request_host(&req)), collection) {
+        return refusal;
+    }
+    // A read-only collection keeps whatever it held in memory; saving it
+    // minus this key would write that over the unreadable data
+    if let Some(refusal) = refuse_if_corrupt(&state, Stored::Collection(collection)) {
+        return refusal;
+    }
+
     {
-        let mut work_shortcuts = state.work_shortcuts.lock().unwrap();
-        if work_shortcuts.remove(key).is_some() {
-            if let Err(e) = save_shortcuts(WORK_SHORTCUTS_FILE, &work_shortcuts) {
-                eprintln!("Failed to save work shortcuts after deletion: {}", e);
-            }
+        let mut saved = state.collection(collection).lock().unwrap();
+        if saved.remove(key).is_some()
+            && let Err(e) = state.storage.save_shortcuts(collection, &saved)
+        {
+            eprintln!("Failed to save {} shortcuts after deletion: {}", collection.name(), e);
         }
     }
+    refresh_claimed_hosts(&state);
+
+    HttpResponse::Found().append_header(("Location", "/")).finish()
+}
+
+// Handler for deleting a wildcard or regex shortcut
+#[post("/delete_pattern")]
+pub async fn delete_pattern(
+    form: Form<DeletePatternForm>,
+    state: Data<Arc<AppState>>,
+) -> impl Responder {
+    let pattern = form.pattern.trim();
+    if pattern.is_empty() {
+        return HttpResponse::BadRequest().body("Pattern cannot be empty.");
+    }
+    if let Some(refusal) = refuse_if_corrupt(&state, Stored::Patterns) {
+        return refusal;
+    }
 
+    let mut patterns = state.pattern_shortcuts.lock().unwrap();
+    let before = patterns.len();
+    patterns.retain(|p| p.shortcut.pattern != pattern);
+    if patterns.len() != before
+        && let Err(e) = state.storage.save_patterns(&patterns)
     {
-        let mut hidden_shortcuts = state.hidden_shortcuts.lock().unwrap();
-        if hidden_shortcuts.remove(key).is_some() {
-            if let Err(e) = save_shortcuts(HIDDEN_SHORTCUTS_FILE, &hidden_shortcuts) {
-                eprintln!("Failed to save hidden shortcuts after deletion: {}", e);
-            }
+        eprintln!("Failed to save pattern shortcuts after deletion: {}", e);
+    }
+
+    HttpResponse::Found().append_header(("Location", "/")).finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::{header, StatusCode}, test::{call_service, init_service, TestRequest}, App};
+    use chrono::DateTime;
+    use std::{thread, time::Duration};
+
+    use crate::config::Config;
+    use crate::storage::Corrupt;
+
+    fn state() -> Arc<AppState> {
+        Arc::new(AppState::for_tests(Config::default()))
+    }
+
+    // Posts `form` to `uri` and returns the status and any redirect location
+    async fn post(state: &Arc<AppState>, uri: &str, form: &[(&str, &str)]) -> (StatusCode, Option<String>) {
+        post_on(state, "go", uri, form).await
+    }
+
+    // `post`, addressed to `host`
+    async fn post_on(state: &Arc<AppState>, host: &str, uri: &str, form: &[(&str, &str)]) -> (StatusCode, Option<String>) {
+        let app = init_service(
+            App::new()
+                .app_data(Data::new(state.clone()))
+                .service(add_shortcut)
+                .service(delete_shortcut)
+                .service(delete_pattern),
+        )
+        .await;
+        let request = TestRequest::post().uri(uri).insert_header(("Host", host)).set_form(form);
+        let response = call_service(&app, request.to_request()).await;
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        (response.status(), location)
+    }
+
+    fn stored_keys(state: &AppState, collection: Collection) -> Vec<String> {
+        let mut keys: Vec<String> = state.storage.load_shortcuts(collection).map(|s| s.into_keys().collect()).unwrap_or_default();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn shadowing_warning_says_which_copy_answers() {
+        let shortcuts: HashMap<String, Shortcut> = serde_json::from_str(r#"{"gh": "https://github.com"}"#).unwrap();
+        let hidden = HashMap::new();
+        let work: HashMap<String, Shortcut> = serde_json::from_str(r#"{"GH": "https://ghe.example.com"}"#).unwrap();
+        let order = [Collection::Shortcuts, Collection::Hidden, Collection::Work];
+        let arranged = arrange(&order, &shortcuts, &hidden, &work);
+        let policy = NormalizationPolicy::default();
+
+        assert_eq!(
+            shadowing_warning(&arranged, Collection::Work, "gh", &policy).as_deref(),
+            Some("go/gh still goes to the shortcuts version (\"gh\"), which is looked up before work.")
+        );
+        assert_eq!(
+            shadowing_warning(&arranged, Collection::Shortcuts, "gh", &policy).as_deref(),
+            Some("This shadows the work version of go/gh (\"GH\").")
+        );
+        assert_eq!(shadowing_warning(&arranged, Collection::Hidden, "wiki", &policy), None);
+    }
+
+    #[actix_web::test]
+    async fn added_shortcut_is_saved_normalized_and_deleted() {
+        let state = state();
+        let (status, location) = post(&state, "/add_shortcut", &[("shortcut", "Git-Hub"), ("url", "https://github.com")]).await;
+        assert_eq!(status, StatusCode::FOUND);
+        assert_eq!(location.as_deref(), Some("/?saved=shortcuts:github"));
+        assert_eq!(stored_keys(&state, Collection::Shortcuts), ["github"]);
+        assert!(state.shortcuts.lock().unwrap()["github"].created_at.is_some());
+
+        let (status, _) = post(&state, "/delete_shortcut", &[("key", "github"), ("collection", "shortcuts")]).await;
+        assert_eq!(status, StatusCode::FOUND);
+        assert!(stored_keys(&state, Collection::Shortcuts).is_empty());
+        assert!(state.shortcuts.lock().unwrap().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn new_shortcuts_go_to_a_collection_the_host_serves() {
+        let state = Arc::new(AppState::for_tests(
+            serde_json::from_str(r#"{"hosts": {"wgo": ["work", "hidden"]}}"#).unwrap(),
+        ));
+
+        let (status, location) = post_on(&state, "wgo", "/add_shortcut", &[("shortcut", "jira"), ("url", "https://jira")]).await;
+        assert_eq!(status, StatusCode::FOUND);
+        assert_eq!(location.as_deref(), Some("/?saved=work:jira"));
+        assert_eq!(stored_keys(&state, Collection::Work), ["jira"]);
+
+        let form = [("shortcut", "gh"), ("url", "https://github.com"), ("collection", "shortcuts")];
+        assert_eq!(post_on(&state, "wgo:8080", "/add_shortcut", &form).await.0, StatusCode::BAD_REQUEST);
+        assert_eq!(post(&state, "/add_shortcut", &form).await.0, StatusCode::FOUND);
+        assert_eq!(stored_keys(&state, Collection::Shortcuts), ["gh"]);
+    }
+
+    #[actix_web::test]
+    async fn hidden_checkbox_saves_to_the_hidden_collection() {
+        let state = state();
+        post(&state, "/add_shortcut", &[("shortcut", "secret"), ("url", "https://x"), ("hidden", "true")]).await;
+        assert_eq!(stored_keys(&state, Collection::Hidden), ["secret"]);
+        assert!(stored_keys(&state, Collection::Shortcuts).is_empty());
+    }
+
+    #[actix_web::test]
+    async fn edit_saves_over_the_original_key_in_its_own_collection() {
+        let state = state();
+        let created: DateTime<Utc> = "2020-01-01T00:00:00Z".parse().unwrap();
+        let mut original = Shortcut::new("https://old");
+        original.created_at = Some(created);
+        state.work_shortcuts.lock().unwrap().insert("My_Key".to_string(), original);
+
+        let form = [("shortcut", "My_Key"), ("url", "https://new"), ("collection", "work")];
+        assert_eq!(post(&state, "/add_shortcut", &form).await.0, StatusCode::FOUND);
+
+        assert_eq!(stored_keys(&state, Collection::Work), ["My_Key"]);
+        assert!(stored_keys(&state, Collection::Shortcuts).is_empty());
+        let work = state.work_shortcuts.lock().unwrap();
+        assert_eq!(work["My_Key"].url, "https://new");
+        assert_eq!(work["My_Key"].created_at, Some(created));
+    }
+
+    #[actix_web::test]
+    async fn delete_only_touches_the_named_collection() {
+        let state = Arc::new(AppState::for_tests(
+            serde_json::from_str(r#"{"hosts": {"wgo": ["work"]}}"#).unwrap(),
+        ));
+        for collection in [&state.shortcuts, &state.work_shortcuts] {
+            collection.lock().unwrap().insert("wiki".to_string(), Shortcut::new("https://wiki"));
         }
+
+        let form = [("key", "wiki"), ("collection", "work")];
+        assert_eq!(post(&state, "/delete_shortcut", &form).await.0, StatusCode::FOUND);
+        assert!(state.work_shortcuts.lock().unwrap().is_empty());
+        assert!(state.shortcuts.lock().unwrap().contains_key("wiki"));
+
+        let form = [("key", "wiki"), ("collection", "shortcuts")];
+        assert_eq!(post_on(&state, "wgo", "/delete_shortcut", &form).await.0, StatusCode::BAD_REQUEST);
+        let form = [("key", "wiki"), ("collection", "everything")];
+        assert_eq!(post(&state, "/delete_shortcut", &form).await.0, StatusCode::BAD_REQUEST);
+        assert!(state.shortcuts.lock().unwrap().contains_key("wiki"));
     }
 
-    {
-        let mut shortcuts = state.shortcuts.lock().unwrap();
-        if shortcuts.remove(key).is_some() {
-            if let Err(e) = save_shortcuts(SHORTCUTS_FILE, &shortcuts) {
-                eprintln!("Failed to save visible shortcuts after deletion: {}", e);
+    #[actix_web::test]
+    async fn alias_and_pattern_deletes_stay_apart() {
+        let state = state();
+        post(&state, "/add_shortcut", &[("shortcut", "docs"), ("url", "https://a")]).await;
+        post(&state, "/add_shortcut", &[("shortcut", "docs"), ("url", "https://b"), ("kind", "wildcard")]).await;
+
+        post(&state, "/delete_shortcut", &[("key", "docs"), ("collection", "shortcuts")]).await;
+        assert!(stored_keys(&state, Collection::Shortcuts).is_empty());
+        assert_eq!(state.pattern_shortcuts.lock().unwrap().len(), 1);
+
+        post(&state, "/delete_pattern", &[("pattern", "docs")]).await;
+        assert!(state.pattern_shortcuts.lock().unwrap().is_empty());
+        assert!(state.storage.load_patterns().is_ok_and(|p| p.is_empty()));
+    }
+
+    #[actix_web::test]
+    async fn read_only_collection_refuses_saves_and_deletes() {
+        let state = state();
+        state.shortcuts.lock().unwrap().insert("gh".to_string(), Shortcut::new("https://github.com"));
+        state.corrupt.lock().unwrap().insert(
+            Stored::Collection(Collection::Shortcuts),
+            Corrupt { source: "shortcuts.json".to_string(), location: Some((1, 1)), message: "bad".to_string(), quarantined: None },
+        );
+
+        let (status, _) = post(&state, "/add_shortcut", &[("shortcut", "new"), ("url", "https://x")]).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, _) = post(&state, "/delete_shortcut", &[("key", "gh"), ("collection", "shortcuts")]).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        assert!(state.shortcuts.lock().unwrap().contains_key("gh"));
+        assert!(state.storage.load_shortcuts(Collection::Shortcuts).is_err());
+    }
+
+    // A redirect locks every collection in search order and holds them while it
+    // works out where to go. A delete running meanwhile must not be holding a
+    // collection further down that order, or each waits on the other forever.
+    #[test]
+    fn delete_running_alongside_a_redirect_holds_no_collection_it_waits_for() {
+        let state = state();
+        state.work_shortcuts.lock().unwrap().insert("gh".to_string(), Shortcut::new("https://github.com"));
+
+        let shortcuts = state.shortcuts.lock().unwrap();
+        let delete = {
+            let state = state.clone();
+            thread::spawn(move || {
+                actix_web::rt::System::new().block_on(post(&state, "/delete_shortcut", &[("key", "gh"), ("collection", "work")])).0
+            })
+        };
+        thread::sleep(Duration::from_millis(50));
+
+        // The rest of the redirect's locks, taken the way it takes them
+        let redirect_got_through = (0..100).any(|_| {
+            let hidden = state.hidden_shortcuts.try_lock();
+            let work = state.work_shortcuts.try_lock();
+            let got_both = hidden.is_ok() && work.is_ok();
+            if !got_both {
+                thread::sleep(Duration::from_millis(20));
             }
-        }
+            got_both
+        });
+        drop(shortcuts);
+
+        assert_eq!(delete.join().unwrap(), StatusCode::FOUND);
+        assert!(redirect_got_through, "the delete kept a collection the redirect needed");
+        assert!(state.work_shortcuts.lock().unwrap().is_empty());
     }
 
-    HttpResponse::Found().append_header(("Location", "/")).finish()
+    #[actix_web::test]
+    async fn empty_fields_are_rejected() {
+        let state = state();
+        let (status, _) = post(&state, "/add_shortcut", &[("shortcut", " "), ("url", "https://x")]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = post(&state, "/delete_shortcut", &[("key", ""), ("collection", "shortcuts")]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
 }
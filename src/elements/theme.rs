@@ -4,18 +4,11 @@ use actix_web::{
     HttpResponse, Responder,
 };
 use serde::Deserialize;
-use std::{
-    collections::HashMap,
-    fs,
-    io,
-    sync::Arc,
-};
+use std::sync::Arc;
 
 use crate::app_state::{AppState, Theme};
-
-// File constants
-pub static THEMES_FILE: &str = "themes.json";
-pub static CURRENT_THEME_FILE: &str = "current_theme.json";
+use crate::elements::refuse_if_corrupt;
+use crate::storage::Stored;
 
 // Helper to define a default dark theme
 pub fn default_dark_theme() -> Theme {
@@ -36,32 +29,6 @@ pub fn default_dark_theme() -> Theme {
     }
 }
 
-// Load saved themes
-pub fn load_themes(path: &str) -> io::Result<HashMap<String, Theme>> {
-    let data = fs::read_to_string(path)?;
-    let map: HashMap<String, Theme> = serde_json::from_str(&data)?;
-    Ok(map)
-}
-
-// Save saved themes
-fn save_themes(path: &str, themes: &HashMap<String, Theme>) -> io::Result<()> {
-    let data = serde_json::to_string_pretty(themes)?;
-    fs::write(path, data)
-}
-
-// Load current theme
-pub fn load_current_theme(path: &str) -> io::Result<Theme> {
-    let data = fs::read_to_string(path)?;
-    let theme: Theme = serde_json::from_str(&data)?;
-    Ok(theme)
-}
-
-// Save current theme
-fn save_current_theme(path: &str, theme: &Theme) -> io::Result<()> {
-    let data = serde_json::to_string_pretty(theme)?;
-    fs::write(path, data)
-}
-
 // Struct to capture the theme form data
 #[derive(Deserialize)]
 pub struct ThemeForm {
@@ -92,6 +59,15 @@ pub async fn save_theme(
     form: Form<ThemeForm>,
     state: Data<Arc<AppState>>,
 ) -> impl Responder {
+    // Every change below saves the current theme, and "save" the theme list too
+    let mut saves = vec![Stored::CurrentTheme];
+    if form.action.as_deref() == Some("save") {
+        saves.push(Stored::Themes);
+    }
+    if let Some(refusal) = saves.into_iter().find_map(|item| refuse_if_corrupt(&state, item)) {
+        return refusal;
+    }
+
     // 1. Handle loading a theme first (if requested via dropdown)
     if let Some(load_name) = form.load_theme_name.clone().filter(|n| !n.is_empty()) {
         let mut current_theme = state.current_theme.lock().unwrap();
@@ -99,7 +75,7 @@ pub async fn save_theme(
 
         if let Some(loaded_theme) = saved_themes.get(&load_name) {
             *current_theme = loaded_theme.clone();
-            if let Err(e) = save_current_theme(CURRENT_THEME_FILE, &current_theme) {
+            if let Err(e) = state.storage.save_current_theme(&current_theme) {
                 eprintln!("Failed to save current theme after loading: {}", e);
             }
         }
@@ -131,22 +107,20 @@ pub async fn save_theme(
         let mut current_theme = state.current_theme.lock().unwrap();
         *current_theme = new_theme.clone();
 
-        if let Err(e) = save_current_theme(CURRENT_THEME_FILE, &current_theme) {
+        if let Err(e) = state.storage.save_current_theme(&current_theme) {
             eprintln!("Failed to save current theme: {}", e);
             return HttpResponse::InternalServerError().body("Failed to save current theme state.");
         }
     }
 
     // 4. Handle saving to saved_themes if action is "save"
-    if let Some(action) = &form.action {
-        if action == "save" {
-            let mut saved_themes = state.saved_themes.lock().unwrap();
-            saved_themes.insert(new_theme.name.clone(), new_theme);
+    if form.action.as_deref() == Some("save") {
+        let mut saved_themes = state.saved_themes.lock().unwrap();
+        saved_themes.insert(new_theme.name.clone(), new_theme);
 
-            if let Err(e) = save_themes(THEMES_FILE, &saved_themes) {
-                eprintln!("Failed to save themes list: {}", e);
-                return HttpResponse::InternalServerError().body("Failed to save themes list.");
-            }
+        if let Err(e) = state.storage.save_themes(&saved_themes) {
+            eprintln!("Failed to save themes list: {}", e);
+            return HttpResponse::InternalServerError().body("Failed to save themes list.");
         }
     }
 
@@ -0,0 +1,173 @@
+// Background health checks for failover shortcuts. Every destination a
+// failover shortcut lists is probed on a timer and the result kept in
+// `AppState.health`, keyed by URL.
+
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use url::{Host, Url};
+
+use crate::app_state::{AppState, Destinations, Shortcut};
+
+/// Health of each URL as last probed; URLs not checked yet are missing.
+pub type HealthMap = HashMap<String, bool>;
+
+/// Every URL a failover shortcut may send people to, primary first.
+pub fn failover_candidates(shortcut: &Shortcut) -> Option<Vec<&str>> {
+    match &shortcut.destinations {
+        Some(Destinations::Failover { alternatives }) => Some(
+            std::iter::once(&shortcut.url)
+                .chain(alternatives)
+                .map(String::as_str)
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+// Collects the distinct failover URLs across all collections
+fn urls_to_probe(state: &AppState) -> Vec<String> {
+    let mut urls = HashSet::new();
+    for collection in [&state.shortcuts, &state.hidden_shortcuts, &state.work_shortcuts] {
+        let collection = collection.lock().unwrap();
+        for shortcut in collection.values() {
+            urls.extend(failover_candidates(shortcut).into_iter().flatten().map(str::to_string));
+        }
+    }
+    urls.into_iter().collect()
+}
+
+/// Checks one destination. Plain `http://` URLs get a HEAD request and count
+/// as healthy unless the server answers 5xx; anything else (e.g. `https://`)
+/// only needs to accept a TCP connection. `None` means the URL has no host to probe.
+pub async fn probe(url: &str, limit: Duration) -> Option<bool> {
+    let parsed = Url::parse(url).ok()?;
+    let host = parsed.host()?;
+    let port = parsed.port_or_known_default()?;
+    // What the URL's authority says, e.g. "[::1]:8080"; the port only when it isn't the default
+    let authority = match parsed.port() {
+        Some(port) => format!("{}:{}", parsed.host_str()?, port),
+        None => parsed.host_str()?.to_string(),
+    };
+
+    let check = async {
+        // IPv6 literals are connected to as addresses, not looked up as "[::1]"
+        let connected = match host {
+            Host::Domain(domain) => TcpStream::connect((domain, port)).await,
+            Host::Ipv4(ip) => TcpStream::connect((IpAddr::V4(ip), port)).await,
+            Host::Ipv6(ip) => TcpStream::connect((IpAddr::V6(ip), port)).await,
+        };
+        let mut stream = connected.ok()?;
+        if parsed.scheme() != "http" {
+            return Some(true);
+        }
+
+        let target = match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        };
+        let request = format!("HEAD {target} HTTP/1.1\r\nHost: {authority}\r\nConnection: close\r\n\r\n");
+        stream.write_all(request.as_bytes()).await.ok()?;
+
+        let mut buffer = [0u8; 64];
+        let read = stream.read(&mut buffer).await.ok()?;
+        let status_line = String::from_utf8_lossy(&buffer[..read]);
+        let status: u16 = status_line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(status < 500)
+    };
+
+    Some(matches!(timeout(limit, check).await, Ok(Some(true))))
+}
+
+/// Probes every failover destination now and then every `interval_secs`,
+/// forever. Each URL is checked in its own task so a slow one can't hold up the rest.
+pub fn spawn_health_checks(state: Arc<AppState>) {
+    let settings = &state.config.health_check;
+    let interval = Duration::from_secs(settings.interval_secs.max(1));
+    let limit = Duration::from_millis(settings.timeout_ms);
+
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+
+            let urls = urls_to_probe(&state);
+            {
+                // Forget URLs no shortcut lists any more
+                let mut health = state.health.lock().unwrap();
+                health.retain(|url, _| urls.contains(url));
+            }
+
+            for url in urls {
+                let state = state.clone();
+                actix_web::rt::spawn(async move {
+                    let Some(healthy) = probe(&url, limit).await else {
+                        return;
+                    };
+                    let mut health = state.health.lock().unwrap();
+                    let previous = health.insert(url.clone(), healthy);
+                    if previous != Some(healthy) && (previous.is_some() || !healthy) {
+                        eprintln!("Health check: {url} is {}", if healthy { "back up" } else { "down" });
+                    }
+                });
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    const LIMIT: Duration = Duration::from_secs(2);
+
+    // Answers one request on a local port with `response`, handing back the request it got
+    async fn serve_once(response: &'static str) -> (u16, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let served = actix_web::rt::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0u8; 1024];
+            let read = stream.read(&mut request).await.unwrap();
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request[..read]).into_owned()
+        });
+        (port, served)
+    }
+
+    #[actix_web::test]
+    async fn server_answering_is_up() {
+        let (port, served) = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").await;
+        assert_eq!(probe(&format!("http://127.0.0.1:{port}/status?full=1"), LIMIT).await, Some(true));
+
+        let request = served.await.unwrap();
+        assert!(request.starts_with("HEAD /status?full=1 HTTP/1.1\r\n"), "{request}");
+        assert!(request.contains(&format!("\r\nHost: 127.0.0.1:{port}\r\n")), "{request}");
+    }
+
+    #[actix_web::test]
+    async fn server_error_is_down() {
+        let (port, served) = serve_once("HTTP/1.1 503 Service Unavailable\r\n\r\n").await;
+        assert_eq!(probe(&format!("http://127.0.0.1:{port}/"), LIMIT).await, Some(false));
+        served.await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn refused_connection_is_down() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        assert_eq!(probe(&format!("http://127.0.0.1:{port}/"), LIMIT).await, Some(false));
+    }
+
+    #[actix_web::test]
+    async fn url_without_host_is_not_probed() {
+        assert_eq!(probe("mailto:someone@example.com", LIMIT).await, None);
+    }
+}
@@ -0,0 +1,150 @@
+// Lets a shortcut answer on its own host name. With `claims_host` set on
+// `jira`, and `jira` resolving to this service, http://jira/123 is handled
+// exactly like go/jira/123.
+
+use actix_web::{
+    dev::ServiceRequest,
+    http::{Method, Uri},
+};
+use std::collections::HashSet;
+
+use crate::app_state::AppState;
+use crate::config::host_name;
+use crate::destinations::request_host;
+use crate::lookup::{arrange, lookup, maps};
+use crate::normalize::normalize_alias;
+
+/// Every alias that claims its host name, normalized. Kept in `AppState` so
+/// requests for other hosts, nearly all of them, never touch the collections.
+pub fn claimed_hosts(state: &AppState) -> HashSet<String> {
+    let policy = &state.config.normalization;
+    [&state.shortcuts, &state.hidden_shortcuts, &state.work_shortcuts]
+        .into_iter()
+        .flat_map(|collection| {
+            collection
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, shortcut)| shortcut.claims_host)
+                .map(|(key, _)| normalize_alias(key, policy))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Brings `AppState::claimed_hosts` up to date; called after anything that
+/// changes the collections.
+pub fn refresh_claimed_hosts(state: &AppState) {
+    let claimed = claimed_hosts(state);
+    *state.claimed_hosts.lock().unwrap() = claimed;
+}
+
+/// The alias that claims `host`, if any. Hosts configured under `hosts` are
+/// alias sets of their own and never claimed.
+pub fn claimed_alias(state: &AppState, host: &str) -> Option<String> {
+    let name = host_name(host).to_lowercase();
+    if name.is_empty() || state.config.hosts.keys().any(|h| h.eq_ignore_ascii_case(&name)) {
+        return None;
+    }
+    if !state.claimed_hosts.lock().unwrap().contains(&normalize_alias(&name, &state.config.normalization)) {
+        return None;
+    }
+
+    let shortcuts = state.shortcuts.lock().unwrap();
+    let hidden_shortcuts = state.hidden_shortcuts.lock().unwrap();
+    let work_shortcuts = state.work_shortcuts.lock().unwrap();
+    let order = state.config.collections_for(Some(host));
+    let arranged = arrange(&order, &shortcuts, &hidden_shortcuts, &work_shortcuts);
+
+    lookup(&maps(&arranged), &name, &state.config.normalization)
+        .filter(|(_, shortcut)| shortcut.claims_host)
+        .map(|(key, _)| key.clone())
+}
+
+/// GET routes the service answers itself on every host, ahead of any alias.
+const SERVICE_PATHS: [&str; 5] = ["/favicon.ico", "/opensearch.xml", "/_suggest", "/_search", "/_backups"];
+
+/// Whether `req` is for the service rather than an alias: its assets, its
+/// own pages, or a form post. Pages shown on a claimed host use these too.
+fn is_for_service(req: &ServiceRequest) -> bool {
+    req.method() != Method::GET || req.path().starts_with("/static/") || SERVICE_PATHS.contains(&req.path())
+}
+
+/// Puts the claimed alias in front of the request path, so routing hands
+/// http://jira/123?x=1 to `go` as /jira/123?x=1. Other requests, and those
+/// meant for the service itself, pass untouched.
+pub fn rewrite_for_claimed_host(req: &mut ServiceRequest, state: &AppState) {
+    if is_for_service(req) {
+        return;
+    }
+    let Some(alias) = request_host(req.request()).and_then(|host| claimed_alias(state, host)) else {
+        return;
+    };
+
+    let path = match req.path() {
+        "/" => String::new(),
+        path => path.to_string(),
+    };
+    let query = req.query_string();
+    let rewritten = if query.is_empty() {
+        format!("/{}{}", alias, path)
+    } else {
+        format!("/{}{}?{}", alias, path, query)
+    };
+
+    if let Ok(uri) = rewritten.parse::<Uri>() {
+        req.match_info_mut().get_mut().update(&uri);
+        req.head_mut().uri = uri;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    // `config.json` contents, with one alias claiming its host and one not
+    fn state(config: &str) -> AppState {
+        let state = AppState::for_tests(serde_json::from_str(config).unwrap());
+        *state.shortcuts.lock().unwrap() = serde_json::from_str(
+            r#"{"jira": {"url": "https://jira.example.com/browse/{*}", "claims_host": true}, "wiki": "https://wiki.example.com"}"#,
+        )
+        .unwrap();
+        refresh_claimed_hosts(&state);
+        state
+    }
+
+    // Where a request for `uri` on `host` is routed
+    fn routed(state: &AppState, host: &str, uri: &str) -> String {
+        let mut req = TestRequest::get().uri(uri).insert_header(("Host", host)).to_srv_request();
+        rewrite_for_claimed_host(&mut req, state);
+        req.uri().to_string()
+    }
+
+    #[test]
+    fn claimed_host_routes_to_its_alias() {
+        let state = state("{}");
+        assert_eq!(routed(&state, "jira:8080", "/ABC-123?x=1"), "/jira/ABC-123?x=1");
+        assert_eq!(routed(&state, "JIRA", "/"), "/jira");
+    }
+
+    #[test]
+    fn other_hosts_pass_untouched() {
+        let state = state(r#"{"hosts": {"jira": ["work"]}}"#);
+        assert_eq!(routed(&state, "wiki", "/x"), "/x");
+        assert_eq!(routed(&state, "go", "/jira"), "/jira");
+        assert_eq!(routed(&state, "jira", "/ABC-123"), "/ABC-123");
+    }
+
+    #[test]
+    fn service_routes_pass_untouched_on_claimed_hosts() {
+        let state = state("{}");
+        assert_eq!(routed(&state, "jira", "/static/style.css"), "/static/style.css");
+        assert_eq!(routed(&state, "jira", "/favicon.ico"), "/favicon.ico");
+        assert_eq!(routed(&state, "jira", "/_search?q=x"), "/_search?q=x");
+
+        let mut req = TestRequest::post().uri("/add_shortcut").insert_header(("Host", "jira")).to_srv_request();
+        rewrite_for_claimed_host(&mut req, &state);
+        assert_eq!(req.uri().to_string(), "/add_shortcut");
+    }
+}
@@ -0,0 +1,344 @@
+// Finding shortcuts across collections and following alias-to-alias chains
+// (a shortcut whose URL is `go:<alias>`).
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::app_state::Shortcut;
+use crate::config::{Collection, NormalizationPolicy};
+use crate::normalize::{find_normalized, normalize_alias};
+
+/// URL prefix marking a shortcut that points at another alias.
+pub const ALIAS_PREFIX: &str = "go:";
+
+/// Longest chain of aliases followed before giving up.
+pub const MAX_CHAIN_DEPTH: usize = 8;
+
+#[derive(Debug)]
+pub enum ChainError {
+    Cycle(Vec<String>),                      // Every alias visited, ending with the repeat
+    TooDeep(Vec<String>),                    // Every alias visited before the limit
+    Missing { from: String, target: String }, // `from` points at an alias that doesn't exist
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::Cycle(path) => write!(f, "Alias chain loops back on itself: {}", path.join(" → ")),
+            ChainError::TooDeep(path) => write!(
+                f,
+                "Alias chain is longer than {} steps: {}",
+                MAX_CHAIN_DEPTH,
+                path.join(" → ")
+            ),
+            ChainError::Missing { from, target } => {
+                write!(f, "Shortcut \"{}\" points at \"{}{}\", which doesn't exist.", from, ALIAS_PREFIX, target)
+            }
+        }
+    }
+}
+
+/// Pairs each collection with its shortcuts, in the given search order.
+pub fn arrange<'a>(
+    order: &[Collection],
+    shortcuts: &'a HashMap<String, Shortcut>,
+    hidden: &'a HashMap<String, Shortcut>,
+    work: &'a HashMap<String, Shortcut>,
+) -> Vec<(Collection, &'a HashMap<String, Shortcut>)> {
+    order
+        .iter()
+        .map(|c| match c {
+            Collection::Shortcuts => (*c, shortcuts),
+            Collection::Hidden => (*c, hidden),
+            Collection::Work => (*c, work),
+        })
+        .collect()
+}
+
+/// Just the maps of arranged collections, for the lookup functions.
+pub fn maps<'a>(arranged: &[(Collection, &'a HashMap<String, Shortcut>)]) -> Vec<&'a HashMap<String, Shortcut>> {
+    arranged.iter().map(|(_, map)| *map).collect()
+}
+
+/// The collections fuzzy matching looks through: hidden ones only when the
+/// `suggestions.include_hidden` setting allows it.
+pub fn searchable<'a>(
+    arranged: &[(Collection, &'a HashMap<String, Shortcut>)],
+    include_hidden: bool,
+) -> Vec<&'a HashMap<String, Shortcut>> {
+    arranged
+        .iter()
+        .filter(|(c, _)| *c != Collection::Hidden || include_hidden)
+        .map(|(_, map)| *map)
+        .collect()
+}
+
+/// Everything but the hidden collection in one map, for the shortcuts table,
+/// with the collection each key came from so edits can be saved back there.
+/// A key in several collections keeps the version lookups reach first.
+pub fn merge_visible(arranged: &[(Collection, &HashMap<String, Shortcut>)]) -> HashMap<String, (Collection, Shortcut)> {
+    let mut merged = HashMap::new();
+    for (collection, map) in arranged.iter().rev().filter(|(c, _)| *c != Collection::Hidden) {
+        merged.extend(map.iter().map(|(k, s)| (k.clone(), (*collection, s.clone()))));
+    }
+    merged
+}
+
+/// Aliases defined in more than one collection, counting keys that normalize
+/// to the same thing. One message per alias, naming the collection that wins.
+pub fn collection_collisions(
+    arranged: &[(Collection, &HashMap<String, Shortcut>)],
+    policy: &NormalizationPolicy,
+) -> Vec<String> {
+    let mut by_alias: BTreeMap<String, Vec<(usize, &String)>> = BTreeMap::new();
+    for (i, (_, map)) in arranged.iter().enumerate() {
+        for key in map.keys() {
+            by_alias.entry(normalize_alias(key, policy)).or_default().push((i, key));
+        }
+    }
+
+    let collections = maps(arranged);
+    by_alias
+        .into_iter()
+        .filter_map(|(alias, mut entries)| {
+            entries.sort();
+            if entries.iter().all(|(i, _)| *i == entries[0].0) {
+                return None;
+            }
+            let (winner, _, _) = lookup_indexed(&collections, &alias, policy)?;
+            let places = entries
+                .iter()
+                .map(|(i, key)| format!("{} (\"{}\")", arranged[*i].0.name(), key))
+                .collect::<Vec<_>>()
+                .join(", ");
+            Some(format!(
+                "go/{} is defined in {}; the {} one wins.",
+                alias,
+                places,
+                arranged[winner].0.name()
+            ))
+        })
+        .collect()
+}
+
+/// The alias a `go:<alias>` URL points at, if it is one.
+pub fn alias_target(url: &str) -> Option<&str> {
+    url.strip_prefix(ALIAS_PREFIX).map(str::trim).filter(|t| !t.is_empty())
+}
+
+/// Looks `key` up in each collection in turn. Exact keys win; otherwise
+/// normalized forms are compared, in the same collection order.
+pub fn lookup<'a>(
+    collections: &[&'a HashMap<String, Shortcut>],
+    key: &str,
+    policy: &NormalizationPolicy,
+) -> Option<(&'a String, &'a Shortcut)> {
+    lookup_indexed(collections, key, policy).map(|(_, key, shortcut)| (key, shortcut))
+}
+
+/// Like `lookup`, but also says which collection (by index) the match came from.
+pub fn lookup_indexed<'a>(
+    collections: &[&'a HashMap<String, Shortcut>],
+    key: &str,
+    policy: &NormalizationPolicy,
+) -> Option<(usize, &'a String, &'a Shortcut)> {
+    let indexed = || collections.iter().enumerate();
+    indexed()
+        .find_map(|(i, c)| c.get_key_value(key).map(|(k, s)| (i, k, s)))
+        .or_else(|| {
+            let wanted = normalize_alias(key, policy);
+            indexed().find_map(|(i, c)| find_normalized(c, &wanted, policy).map(|(k, s)| (i, k, s)))
+        })
+}
+
+/// Follows `go:` links starting from `start` (saved under `start_key`) until
+/// reaching a shortcut with a real URL.
+pub fn follow_chain(
+    collections: &[&HashMap<String, Shortcut>],
+    start_key: &str,
+    start: &Shortcut,
+    policy: &NormalizationPolicy,
+) -> Result<Shortcut, ChainError> {
+    let steps = chain_steps(collections, start_key, start, policy)?;
+    Ok(steps.last().map_or(start, |(_, _, shortcut)| shortcut).clone())
+}
+
+/// Every alias a chain passes through after `start`, with the index of the
+/// collection each was found in. Empty when `start` has a real URL.
+pub fn chain_steps<'a>(
+    collections: &[&'a HashMap<String, Shortcut>],
+    start_key: &str,
+    start: &Shortcut,
+    policy: &NormalizationPolicy,
+) -> Result<Vec<(usize, &'a String, &'a Shortcut)>, ChainError> {
+    let mut visited = vec![start_key.to_string()];
+    let mut steps = Vec::new();
+    let mut current_url = &start.url;
+
+    while let Some(target) = alias_target(current_url) {
+        let Some((index, key, next)) = lookup_indexed(collections, target, policy) else {
+            return Err(ChainError::Missing {
+                from: visited.last().cloned().unwrap_or_default(),
+                target: target.to_string(),
+            });
+        };
+
+        let seen = visited.contains(key);
+        visited.push(key.clone());
+        if seen {
+            return Err(ChainError::Cycle(visited));
+        }
+        if visited.len() > MAX_CHAIN_DEPTH {
+            return Err(ChainError::TooDeep(visited));
+        }
+        steps.push((index, key, next));
+        current_url = &next.url;
+    }
+
+    Ok(steps)
+}
+
+/// Every alias, in any collection, that ends up at the same URL as `target`,
+/// directly or through a chain. Sorted by collection, then key.
+pub fn synonyms<'a>(
+    collections: &[&'a HashMap<String, Shortcut>],
+    target: &Shortcut,
+    policy: &NormalizationPolicy,
+) -> Vec<(usize, &'a String)> {
+    let mut found: Vec<(usize, &String)> = collections
+        .iter()
+        .enumerate()
+        .flat_map(|(i, c)| c.iter().map(move |(key, shortcut)| (i, key, shortcut)))
+        .filter(|(_, key, shortcut)| {
+            follow_chain(collections, key, shortcut, policy).is_ok_and(|end| end.url == target.url)
+        })
+        .map(|(i, key, _)| (i, key))
+        .collect();
+    found.sort();
+    found
+}
+
+/// Every broken chain in the saved data, one message per problem. A cycle is
+/// reported once no matter which of its aliases it was reached from.
+pub fn chain_problems(collections: &[&HashMap<String, Shortcut>], policy: &NormalizationPolicy) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut seen_cycles: Vec<Vec<String>> = Vec::new();
+
+    let mut starts: Vec<(&String, &Shortcut)> = collections
+        .iter()
+        .flat_map(|c| c.iter())
+        .filter(|(_, s)| alias_target(&s.url).is_some())
+        .collect();
+    starts.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (key, shortcut) in starts {
+        match follow_chain(collections, key, shortcut, policy) {
+            Ok(_) => {}
+            Err(ChainError::Cycle(path)) => {
+                let repeated = path.last().cloned().unwrap_or_default();
+                let start = path.iter().position(|k| *k == repeated).unwrap_or(0);
+                let mut members = path[start..path.len() - 1].to_vec();
+                members.sort();
+                if !seen_cycles.contains(&members) {
+                    seen_cycles.push(members);
+                    problems.push(ChainError::Cycle(path[start..].to_vec()).to_string());
+                }
+            }
+            Err(e @ ChainError::Missing { .. }) => {
+                let message = e.to_string();
+                if !problems.contains(&message) {
+                    problems.push(message);
+                }
+            }
+            Err(e) => problems.push(e.to_string()),
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A collection from `{"alias": "url", ...}` JSON
+    fn collection(json: &str) -> HashMap<String, Shortcut> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn chain_steps_follow_aliases_across_collections() {
+        let personal = collection(r#"{"docs": "go:wiki"}"#);
+        let work = collection(r#"{"wiki": "go:Team-Wiki", "teamwiki": "https://wiki.example.com"}"#);
+        let collections = [&personal, &work];
+        let policy = NormalizationPolicy::default();
+
+        let steps = chain_steps(&collections, "docs", &personal["docs"], &policy).unwrap();
+        let visited: Vec<(usize, &str)> = steps.iter().map(|(i, k, _)| (*i, k.as_str())).collect();
+        assert_eq!(visited, [(1, "wiki"), (1, "teamwiki")]);
+        assert_eq!(steps.last().unwrap().2.url, "https://wiki.example.com");
+
+        assert!(chain_steps(&collections, "teamwiki", &work["teamwiki"], &policy).unwrap().is_empty());
+    }
+
+    #[test]
+    fn chain_steps_report_cycles_and_missing_targets() {
+        let shortcuts = collection(r#"{"a": "go:b", "b": "go:a", "c": "go:nowhere"}"#);
+        let collections = [&shortcuts];
+        let policy = NormalizationPolicy::default();
+
+        match chain_steps(&collections, "a", &shortcuts["a"], &policy) {
+            Err(ChainError::Cycle(path)) => assert_eq!(path, ["a", "b", "a"]),
+            _ => panic!("expected a cycle"),
+        }
+        match chain_steps(&collections, "c", &shortcuts["c"], &policy) {
+            Err(ChainError::Missing { from, target }) => assert_eq!((from.as_str(), target.as_str()), ("c", "nowhere")),
+            _ => panic!("expected a missing target"),
+        }
+    }
+
+    #[test]
+    fn chain_steps_give_up_on_long_chains() {
+        let json = (0..=MAX_CHAIN_DEPTH)
+            .map(|i| format!(r#""a{}": "go:a{}""#, i, i + 1))
+            .chain(std::iter::once(format!(r#""a{}": "https://end""#, MAX_CHAIN_DEPTH + 1)))
+            .collect::<Vec<_>>()
+            .join(",");
+        let shortcuts = collection(&format!("{{{}}}", json));
+        let result = chain_steps(&[&shortcuts], "a0", &shortcuts["a0"], &NormalizationPolicy::default());
+        assert!(matches!(result, Err(ChainError::TooDeep(_))));
+    }
+
+    #[test]
+    fn chain_problems_lists_each_cycle_once() {
+        let shortcuts = collection(
+            r#"{"a": "go:b", "b": "go:c", "c": "go:a", "d": "go:gone", "e": "go:gone", "ok": "go:site", "site": "https://x"}"#,
+        );
+        let problems = chain_problems(&[&shortcuts], &NormalizationPolicy::default());
+        assert_eq!(
+            problems,
+            [
+                "Alias chain loops back on itself: a → b → c → a",
+                "Shortcut \"d\" points at \"go:gone\", which doesn't exist.",
+                "Shortcut \"e\" points at \"go:gone\", which doesn't exist.",
+            ]
+        );
+    }
+
+    #[test]
+    fn collection_collisions_name_every_copy_and_the_winner() {
+        let shortcuts = collection(r#"{"GitHub": "https://github.com", "gh": "https://github.com"}"#);
+        let hidden = collection(r#"{"Gh": "https://ghe.example.com"}"#);
+        let work = collection(r#"{"github": "https://ghe.example.com", "wiki": "https://wiki.example.com"}"#);
+        let order = [Collection::Shortcuts, Collection::Hidden, Collection::Work];
+        let arranged = arrange(&order, &shortcuts, &hidden, &work);
+
+        let collisions = collection_collisions(&arranged, &NormalizationPolicy::default());
+        assert_eq!(
+            collisions,
+            [
+                "go/gh is defined in shortcuts (\"gh\"), hidden (\"Gh\"); the shortcuts one wins.",
+                "go/github is defined in shortcuts (\"GitHub\"), work (\"github\"); the work one wins.",
+            ]
+        );
+    }
+}
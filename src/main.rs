@@ -1,48 +1,106 @@
 mod app_state;
+mod balance;
 mod base_page;
+mod config;
+mod destinations;
 mod elements;
+mod health;
+mod hosts;
+mod lookup;
+mod normalize;
 mod pages;
+mod patterns;
+mod resolve;
+mod schedule;
+mod storage;
+mod suggest;
 
-use actix_files::Files;
+use actix_files::{Files, NamedFile};
 use actix_web::{
+    dev::Service,
     get,
-    web::Data,
-    App, HttpResponse, HttpServer, Responder,
+    web::{Data, Query},
+    App, HttpRequest, HttpResponse, HttpServer,
 };
+use serde::Deserialize;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::Path,
-    fs,
     sync::{Arc, Mutex},
 };
 
 use app_state::AppState;
+use config::Collection;
+use storage::{report_load_error, CorruptMap, Stored};
 
-use pages::not_found::{go, render_shortcuts_table};
+use pages::backups::backups;
+use pages::search::search;
+use pages::opensearch::{opensearch_description, suggestions};
+use pages::not_found::{
+    go, resolve, render_chain_problems, render_collisions, render_corrupt, render_expiring, render_notice,
+    render_patterns_table, render_shortcuts_table,
+};
+use destinations::{request_host, PickContext};
 use elements::theme::save_theme;
-use elements::shortcut::{add_shortcut, delete_shortcut};
+use elements::shortcut::{add_shortcut, delete_pattern, delete_shortcut, shadowing_warning};
+use elements::backup::restore_backup;
 use base_page::{render_base_page, render_add_shortcut_button, render_add_shortcut_modal, nav_bar_html};
 
-static SHORTCUTS_FILE: &str = "shortcuts.json";
-static HIDDEN_SHORTCUTS_FILE: &str = "hidden-shortcuts.json";
-static WORK_SHORTCUTS_FILE: &str = "work-shortcuts.json";
+static CONFIG_FILE: &str = "config.json";
 
-fn load_shortcuts(path: &str) -> std::io::Result<HashMap<String, String>> {
-    let data = fs::read_to_string(path)?;
-    let map: HashMap<String, String> = serde_json::from_str(&data)?;
-    Ok(map)
+#[derive(Deserialize)]
+struct IndexQuery {
+    q: Option<String>,       // Typed into a browser search box with go as the engine
+    saved: Option<String>, // "<collection>:<alias>" just saved by the add form, to check for shadowing
 }
 
 #[get("/")]
-async fn index(state: Data<Arc<AppState>>) -> impl Responder {
+async fn index(req: HttpRequest, query: Query<IndexQuery>, state: Data<Arc<AppState>>) -> HttpResponse {
+    // `/?q=gh foo` is handled exactly like `/gh foo`, passing on any other query keys
+    if let Some(q) = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        let rest = req
+            .query_string()
+            .split('&')
+            .filter(|pair| *pair != "q" && !pair.starts_with("q="))
+            .collect::<Vec<_>>()
+            .join("&");
+        return resolve(&req, q.to_string(), &rest, &state);
+    }
+
     let shortcuts = state.shortcuts.lock().unwrap();
+    let hidden_shortcuts = state.hidden_shortcuts.lock().unwrap();
     let work_shortcuts = state.work_shortcuts.lock().unwrap();
     let current_theme = state.current_theme.lock().unwrap();
 
-    let mut combined_shortcuts = shortcuts.clone();
-    combined_shortcuts.extend(work_shortcuts.clone());
+    // Only the collections this host name serves
+    let order = state.config.collections_for(request_host(&req));
+    let arranged = lookup::arrange(&order, &shortcuts, &hidden_shortcuts, &work_shortcuts);
+    let combined_shortcuts = lookup::merge_visible(&arranged);
+
+    // Rebuilt here rather than passed in the URL, so a link can't put words in the box
+    let notice = query
+        .saved
+        .as_deref()
+        .and_then(|saved| saved.split_once(':'))
+        .and_then(|(name, alias)| {
+            let collection = Collection::ALL.into_iter().find(|c| c.name() == name)?;
+            shadowing_warning(&arranged, collection, alias, &state.config.normalization)
+        });
+
+    let chain_problems = lookup::chain_problems(&lookup::maps(&arranged), &state.config.normalization);
+    let collisions = lookup::collection_collisions(&arranged, &state.config.normalization);
 
-    let table_html = render_shortcuts_table(&combined_shortcuts);
+    let context = PickContext::new(&req, &state);
+    let table_html = format!(
+        "{}{}{}{}{}{}{}",
+        render_corrupt(&state.corrupt.lock().unwrap()),
+        render_notice(notice.as_deref()),
+        render_chain_problems(&chain_problems),
+        render_collisions(&collisions),
+        render_expiring(&combined_shortcuts, context.now.date(), state.config.lifecycle.expiring_soon_days),
+        render_shortcuts_table(&combined_shortcuts, &context),
+        render_patterns_table(&state.pattern_shortcuts.lock().unwrap())
+    );
     let saved_themes = state.saved_themes.lock().unwrap();
 
     let nav_with_button = nav_bar_html()
@@ -67,33 +125,100 @@ async fn index(state: Data<Arc<AppState>>) -> impl Responder {
         .body(final_html)
 }
 
+// Browsers ask for this on their own; serve it so it never reaches the alias fallback
+#[get("/favicon.ico")]
+async fn favicon() -> std::io::Result<NamedFile> {
+    NamedFile::open("static/favicon.ico")
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let shortcuts = load_shortcuts(SHORTCUTS_FILE).unwrap_or_else(|e| {
-        eprintln!("Failed to load {SHORTCUTS_FILE}: {e}");
-        HashMap::new()
-    });
+    // No config.json means default settings. One that can't be read or parsed
+    // stops startup: running on defaults would ignore it, storage backend included.
+    let config = match config::load_config(CONFIG_FILE) {
+        Ok(config) => config,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            eprintln!("No {CONFIG_FILE} found. Using default settings.");
+            config::Config::default()
+        }
+        Err(e) => {
+            eprintln!("Failed to load {CONFIG_FILE}: {e}");
+            std::process::exit(1);
+        }
+    };
 
-    let hidden_shortcuts = load_shortcuts(HIDDEN_SHORTCUTS_FILE).unwrap_or_else(|e| {
-        eprintln!("Failed to load {HIDDEN_SHORTCUTS_FILE}: {e}");
-        HashMap::new()
+    if let Some(tz) = &config.timezone
+        && tz.parse::<chrono_tz::Tz>().is_err()
+    {
+        eprintln!("{CONFIG_FILE}: unknown timezone \"{tz}\". Scheduled shortcuts will use server local time.");
+    }
+
+    for (name, ranges) in &config.networks {
+        for range in ranges.iter().filter(|r| r.parse::<ipnet::IpNet>().is_err()) {
+            eprintln!("{CONFIG_FILE}: network \"{name}\" has invalid CIDR range \"{range}\"; it will be ignored.");
+        }
+    }
+
+    let storage = storage::open(&config.storage).unwrap_or_else(|e| {
+        eprintln!("Failed to open storage: {e}");
+        std::process::exit(1);
     });
 
-    let work_shortcuts = load_shortcuts(WORK_SHORTCUTS_FILE).unwrap_or_else(|e| {
-        eprintln!("Failed to load {WORK_SHORTCUTS_FILE}: {e}");
-        HashMap::new()
+    // Anything that exists but doesn't parse starts out empty and read-only,
+    // rather than being overwritten by the next save
+    let mut corrupt = CorruptMap::new();
+
+    let mut load_collection = |collection: Collection| {
+        storage.load_shortcuts(collection).unwrap_or_else(|e| {
+            report_load_error(Stored::Collection(collection), &e, &mut corrupt);
+            HashMap::new()
+        })
+    };
+    let shortcuts = load_collection(Collection::Shortcuts);
+    let hidden_shortcuts = load_collection(Collection::Hidden);
+    let work_shortcuts = load_collection(Collection::Work);
+
+    let pattern_shortcuts = storage.load_patterns().unwrap_or_else(|e| {
+        report_load_error(Stored::Patterns, &e, &mut corrupt);
+        Vec::new()
     });
 
-    let saved_themes = elements::theme::load_themes("themes.json").unwrap_or_else(|e| {
-        eprintln!("Failed to load themes.json: {e}. Creating default map.");
+    for pattern in pattern_shortcuts.iter().filter(|p| p.regex.is_none()) {
+        if let Err(e) = patterns::compile(&pattern.shortcut) {
+            eprintln!("Pattern \"{}\" is invalid and will never match: {e}", pattern.shortcut.pattern);
+        }
+    }
+
+    for (collection, map) in [
+        (Collection::Shortcuts, &shortcuts),
+        (Collection::Hidden, &hidden_shortcuts),
+        (Collection::Work, &work_shortcuts),
+    ] {
+        for (normalized, keys) in normalize::find_collisions(map, &config.normalization) {
+            eprintln!(
+                "{} collection: keys {} all normalize to \"{normalized}\"; only \"{}\" is reachable by its normalized form.",
+                collection.name(),
+                keys.join(", "),
+                keys[0]
+            );
+        }
+    }
+
+    let arranged = lookup::arrange(&config.search_order(), &shortcuts, &hidden_shortcuts, &work_shortcuts);
+    for collision in lookup::collection_collisions(&arranged, &config.normalization) {
+        eprintln!("{collision}");
+    }
+
+    let saved_themes = storage.load_themes().unwrap_or_else(|e| {
+        report_load_error(Stored::Themes, &e, &mut corrupt);
         let mut map = HashMap::new();
         let default = elements::theme::default_dark_theme();
         map.insert(default.name.clone(), default);
         map
     });
 
-    let current_theme = elements::theme::load_current_theme("current_theme.json").unwrap_or_else(|e| {
-        eprintln!("Failed to load current_theme.json: {e}. Using default theme.");
+    let current_theme = storage.load_current_theme().unwrap_or_else(|e| {
+        report_load_error(Stored::CurrentTheme, &e, &mut corrupt);
         saved_themes.get("Dark Default").cloned().unwrap_or_else(elements::theme::default_dark_theme)
     });
 
@@ -101,20 +226,42 @@ async fn main() -> std::io::Result<()> {
         shortcuts: Mutex::new(shortcuts),
         hidden_shortcuts: Mutex::new(hidden_shortcuts),
         work_shortcuts: Mutex::new(work_shortcuts),
+        pattern_shortcuts: Mutex::new(pattern_shortcuts),
+        health: Mutex::new(HashMap::new()),
+        balance: Mutex::new(HashMap::new()),
+        claimed_hosts: Mutex::new(HashSet::new()),
         current_theme: Mutex::new(current_theme),
         saved_themes: Mutex::new(saved_themes),
+        storage,
+        corrupt: Mutex::new(corrupt),
+        config,
     });
 
+    hosts::refresh_claimed_hosts(&state);
+    health::spawn_health_checks(state.clone());
+
     let _ = Path::new(".");
 
     HttpServer::new(move || {
+        let dispatch_state = state.clone();
         App::new()
             .app_data(Data::new(state.clone()))
+            .wrap_fn(move |mut req, srv| {
+                hosts::rewrite_for_claimed_host(&mut req, &dispatch_state);
+                srv.call(req)
+            })
             .service(index)
             .service(Files::new("/static", "./static").prefer_utf8(true))
+            .service(favicon)
+            .service(search)
+            .service(opensearch_description)
+            .service(suggestions)
             .service(add_shortcut)
             .service(delete_shortcut)
+            .service(delete_pattern)
             .service(save_theme)
+            .service(backups)
+            .service(restore_backup)
             .service(go)
     })
     .bind(("0.0.0.0", 80))?
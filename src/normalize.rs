@@ -0,0 +1,96 @@
+// Canonical forms of alias keys, so near-identical spellings resolve to the
+// same shortcut.
+
+use std::collections::HashMap;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::config::NormalizationPolicy;
+
+/// Applies `policy` to an alias. Used when saving a new shortcut and when
+/// looking one up, so both sides agree on the canonical form.
+pub fn normalize_alias(alias: &str, policy: &NormalizationPolicy) -> String {
+    let mut out: String = if policy.unicode_nfc {
+        alias.nfc().collect()
+    } else {
+        alias.to_string()
+    };
+
+    if policy.strip_trailing_slash {
+        out.truncate(out.trim_end_matches('/').len());
+    }
+    if policy.case_fold {
+        out = out.to_lowercase();
+    }
+    if policy.fold_separators {
+        out.retain(|c| c != '-' && c != '_');
+    }
+    out
+}
+
+// Among keys sharing a normalized form, one already in that form wins, then
+// the alphabetically first, so the choice never depends on hash order.
+fn preference<'a>(key: &'a str, normalized: &str) -> (bool, &'a str) {
+    (key != normalized, key)
+}
+
+/// Finds the key in `map` whose normalized form equals `wanted` (which must
+/// already be normalized).
+pub fn find_normalized<'a, V>(
+    map: &'a HashMap<String, V>,
+    wanted: &str,
+    policy: &NormalizationPolicy,
+) -> Option<(&'a String, &'a V)> {
+    map.iter()
+        .filter(|(key, _)| normalize_alias(key, policy) == wanted)
+        .min_by_key(|(key, _)| preference(key, wanted))
+}
+
+/// Groups keys that become identical once normalized. Each entry is the
+/// normalized form and the original keys sharing it, with the one lookups
+/// resolve to first.
+pub fn find_collisions<V>(map: &HashMap<String, V>, policy: &NormalizationPolicy) -> Vec<(String, Vec<String>)> {
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    for key in map.keys() {
+        groups.entry(normalize_alias(key, policy)).or_default().push(key.clone());
+    }
+
+    let mut collisions: Vec<(String, Vec<String>)> = groups
+        .into_iter()
+        .filter(|(_, keys)| keys.len() > 1)
+        .map(|(normalized, mut keys)| {
+            keys.sort_by(|a, b| preference(a, &normalized).cmp(&preference(b, &normalized)));
+            (normalized, keys)
+        })
+        .collect();
+    collisions.sort();
+    collisions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_policy_folds_case_separators_and_trailing_slash() {
+        let policy = NormalizationPolicy::default();
+        assert_eq!(normalize_alias("Git-Hub_Repo/", &policy), "githubrepo");
+        assert_eq!(normalize_alias("gh", &policy), "gh");
+    }
+
+    #[test]
+    fn composed_and_decomposed_accents_match() {
+        let policy = NormalizationPolicy::default();
+        assert_eq!(normalize_alias("cafe\u{301}", &policy), normalize_alias("caf\u{e9}", &policy));
+    }
+
+    #[test]
+    fn disabled_rules_leave_the_alias_alone() {
+        let policy = NormalizationPolicy {
+            case_fold: false,
+            fold_separators: false,
+            unicode_nfc: false,
+            strip_trailing_slash: false,
+        };
+        assert_eq!(normalize_alias("Git-Hub_Repo/", &policy), "Git-Hub_Repo/");
+    }
+}
@@ -0,0 +1,63 @@
+use actix_web::{get, web::Data, HttpResponse, Responder};
+use htmlescape::encode_minimal;
+use std::sync::Arc;
+
+use crate::app_state::AppState;
+use crate::base_page::render_base_page;
+use crate::pages::not_found::render_corrupt;
+
+/// Lists the earlier versions storage has kept, each with a button to put it back.
+#[get("/_backups")]
+pub async fn backups(state: Data<Arc<AppState>>) -> impl Responder {
+    let current_theme = state.current_theme.lock().unwrap();
+    let saved_themes = state.saved_themes.lock().unwrap();
+
+    let listing = match state.storage.list_backups() {
+        Err(e) => format!("<p>No backups are available: {}.</p>", encode_minimal(&e.to_string())),
+        Ok(backups) if backups.is_empty() => {
+            "<p>No backups yet. One is kept each time a file is saved.</p>".to_string()
+        }
+        Ok(backups) => {
+            let rows = backups
+                .iter()
+                .map(|b| {
+                    format!(
+                        r#"<tr><td>{name}</td><td>{generation}</td><td>{saved}</td><td>{bytes} bytes</td><td>
+                    <form action="/restore_backup" method="POST" style="display:inline;" onsubmit="return confirm('Replace {name} with backup {generation}? The current version is kept as a backup.');">
+                        <input type="hidden" name="name" value="{name}">
+                        <input type="hidden" name="generation" value="{generation}">
+                        <button type="submit">Restore</button>
+                    </form></td></tr>"#,
+                        name = encode_minimal(&b.name),
+                        generation = b.generation,
+                        saved = b.saved.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_default(),
+                        bytes = b.bytes,
+                    )
+                })
+                .collect::<String>();
+            format!(
+                r#"<table class="grid">
+      <thead><tr><th>File</th><th>Generation</th><th>Saved</th><th>Size</th><th></th></tr></thead>
+      <tbody>{}</tbody>
+    </table>"#,
+                rows
+            )
+        }
+    };
+
+    let content = format!(
+        r#"
+    <h1>Backups</h1>
+    {corrupt}
+    <p>Generation 1 is the version that was replaced most recently. Restoring one
+    makes it current again and keeps what it replaces as a new backup.</p>
+    {listing}
+    <p><a href="/">Back to all shortcuts</a></p>
+    "#,
+        corrupt = render_corrupt(&state.corrupt.lock().unwrap())
+    );
+
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(render_base_page("Backups", &content, &current_theme, &saved_themes))
+}
@@ -0,0 +1,25 @@
+use htmlescape::encode_minimal;
+use std::collections::HashMap;
+
+use crate::app_state::Theme;
+use crate::base_page::render_base_page;
+
+/// Renders a themed page explaining why a shortcut couldn't be followed.
+pub fn error_page(
+    heading: &str,
+    message: &str,
+    current_theme: &Theme,
+    saved_themes: &HashMap<String, Theme>
+) -> String {
+    let content = format!(
+        r#"
+    <h1>{heading}</h1>
+    <p>{message}</p>
+    <p><a href="/">Back to all shortcuts</a></p>
+    "#,
+        heading = encode_minimal(heading),
+        message = encode_minimal(message)
+    );
+
+    render_base_page(heading, &content, current_theme, saved_themes)
+}
@@ -0,0 +1,75 @@
+use htmlescape::encode_minimal;
+use std::collections::HashMap;
+
+use crate::app_state::Theme;
+use crate::base_page::render_base_page;
+use crate::resolve::is_web_url;
+
+/// Page served for a multi-tab shortcut. It opens every URL after the first in
+/// a new tab and then sends this tab to the first. If the browser blocks the
+/// popups it stays put and offers an "Open all" button instead, since a click
+/// is allowed to open tabs. URLs other than http(s) are only listed as text,
+/// never linked or opened by the script.
+pub fn launch_page(
+    alias: &str,
+    urls: &[String],
+    current_theme: &Theme,
+    saved_themes: &HashMap<String, Theme>
+) -> String {
+    let items = urls
+        .iter()
+        .map(|u| {
+            if is_web_url(u) {
+                format!("<li><a href=\"{0}\" target=\"_blank\" rel=\"noopener\">{0}</a></li>", encode_minimal(u))
+            } else {
+                format!("<li><span class=\"url\">{}</span></li>", encode_minimal(u))
+            }
+        })
+        .collect::<String>();
+
+    // What the script opens: the rest in new tabs, then the first in this one.
+    // Both are kept from closing the <script> tag early.
+    let new_tabs: Vec<&String> = urls.iter().skip(1).filter(|u| is_web_url(u)).collect();
+    let new_tabs_json = serde_json::to_string(&new_tabs)
+        .unwrap_or_else(|_| "[]".to_string())
+        .replace("</", "<\\/");
+    let here_json = serde_json::to_string(&urls.first().filter(|u| is_web_url(u)))
+        .unwrap_or_else(|_| "null".to_string())
+        .replace("</", "<\\/");
+
+    let content = format!(
+        r#"
+    <h1>Opening {count} tabs for {alias}</h1>
+    <p id="popup-blocked" style="display: none;">Your browser blocked the extra tabs. Use the button to open them all, or click each link.</p>
+    <button type="button" id="open-all">Open all</button>
+    <ol>{items}</ol>
+    <script>
+      (function() {{
+        const newTabs = {new_tabs_json};
+        const here = {here_json};
+        function openAll() {{
+          let opened = 0;
+          newTabs.forEach((u) => {{
+            const tab = window.open(u, '_blank');
+            if (tab) {{
+              tab.opener = null;
+              opened++;
+            }}
+          }});
+          if (opened < newTabs.length) {{
+            document.getElementById('popup-blocked').style.display = 'block';
+          }} else if (here) {{
+            window.location.replace(here);
+          }}
+        }}
+        document.getElementById('open-all').addEventListener('click', openAll);
+        openAll();
+      }})();
+    </script>
+    "#,
+        count = urls.len(),
+        alias = encode_minimal(alias),
+    );
+
+    render_base_page(&format!("Opening {}", encode_minimal(alias)), &content, current_theme, saved_themes)
+}
@@ -0,0 +1,107 @@
+use htmlescape::encode_minimal;
+use percent_encoding::utf8_percent_encode;
+use std::collections::HashMap;
+
+use crate::app_state::{Shortcut, Theme};
+use crate::base_page::render_base_page;
+use crate::resolve::{is_web_url, PATH_SEGMENT};
+
+// "go/x" link to another alias
+fn alias_link(alias: &str) -> String {
+    format!(
+        "<a href=\"/{}\">go/{}</a>",
+        encode_minimal(&utf8_percent_encode(alias, PATH_SEGMENT).to_string()),
+        encode_minimal(alias)
+    )
+}
+
+/// Served instead of a redirect once a shortcut has passed its expiry date.
+pub fn expired_page(
+    alias: &str,
+    shortcut: &Shortcut,
+    current_theme: &Theme,
+    saved_themes: &HashMap<String, Theme>
+) -> String {
+    let expired_on = shortcut.expires.map(|d| format!(" on {}", d)).unwrap_or_default();
+    let owner = match &shortcut.owner {
+        Some(owner) => format!("<p>This link belonged to <strong>{}</strong>. Ask them if you still need it.</p>", encode_minimal(owner)),
+        None => "<p>No owner was recorded for this link.</p>".to_string(),
+    };
+    let instead = shortcut
+        .deprecated
+        .as_deref()
+        .map(|replacement| format!("<p>Try {} instead.</p>", alias_link(replacement)))
+        .unwrap_or_default();
+
+    let content = format!(
+        r#"
+    <h1>go/{alias} is gone</h1>
+    <p>This shortcut expired{expired_on} and no longer redirects anywhere.</p>
+    {owner}
+    {instead}
+    <p><a href="/">Back to all shortcuts</a></p>
+    "#,
+        alias = encode_minimal(alias),
+    );
+
+    render_base_page("Shortcut Expired", &content, current_theme, saved_themes)
+}
+
+/// Countdown shown before forwarding through a deprecated shortcut, so people
+/// learn the new alias without losing their way. Destinations other than
+/// http(s) are shown as text instead of counted down to.
+pub fn deprecated_page(
+    alias: &str,
+    replacement: &str,
+    destination: &str,
+    countdown_secs: u32,
+    current_theme: &Theme,
+    saved_themes: &HashMap<String, Theme>
+) -> String {
+    if !is_web_url(destination) {
+        let content = format!(
+            r#"
+    <h1>go/{alias} is deprecated</h1>
+    <p>Use {replacement} from now on.</p>
+    <p>It points to <span id="destination" class="url">{destination}</span>.</p>
+    "#,
+            alias = encode_minimal(alias),
+            replacement = alias_link(replacement),
+            destination = encode_minimal(destination),
+        );
+        return render_base_page("Shortcut Deprecated", &content, current_theme, saved_themes);
+    }
+
+    // Keep the URL from closing the <script> tag early
+    let destination_json = serde_json::to_string(destination)
+        .unwrap_or_else(|_| "\"/\"".to_string())
+        .replace("</", "<\\/");
+
+    let content = format!(
+        r#"
+    <h1>go/{alias} is deprecated</h1>
+    <p>Use {replacement} from now on.</p>
+    <p>Forwarding to <a id="destination" href="{href}">{href}</a> in <span id="countdown">{countdown_secs}</span> seconds&hellip;</p>
+    <script>
+      (function() {{
+        let remaining = {countdown_secs};
+        const tick = () => {{
+          if (remaining <= 0) {{
+            window.location.replace({destination_json});
+            return;
+          }}
+          document.getElementById('countdown').textContent = remaining;
+          remaining--;
+          setTimeout(tick, 1000);
+        }};
+        tick();
+      }})();
+    </script>
+    "#,
+        alias = encode_minimal(alias),
+        replacement = alias_link(replacement),
+        href = encode_minimal(destination),
+    );
+
+    render_base_page("Shortcut Deprecated", &content, current_theme, saved_themes)
+}
@@ -1 +1,8 @@
+pub mod backups;
+pub mod error;
+pub mod launch;
+pub mod lifecycle;
 pub mod not_found;
+pub mod opensearch;
+pub mod preview;
+pub mod search;
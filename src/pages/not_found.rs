@@ -1,41 +1,238 @@
-use actix_web::{get, web::{self, Data}, HttpResponse, Responder};
+use actix_web::{
+    get,
+    http::{header::{HeaderName, HeaderValue}, StatusCode},
+    web::{self, Data},
+    HttpRequest, HttpResponse, Responder,
+};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use htmlescape::encode_minimal;
+use percent_encoding::utf8_percent_encode;
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::app_state::AppState;
+use crate::app_state::{AppState, Destinations, QueryPrecedence, Shortcut};
 use crate::app_state::Theme;
+use crate::config::{Collection, FallbackMode};
 use crate::base_page::{render_base_page, render_add_shortcut_button, render_add_shortcut_modal, nav_bar_html};
+use crate::lookup::{
+    arrange, chain_steps, follow_chain, lookup, lookup_indexed, maps, merge_visible, searchable, synonyms, ChainError,
+};
+use crate::pages::error::error_page;
+use crate::pages::launch::launch_page;
+use crate::pages::lifecycle::{deprecated_page, expired_page};
+use crate::pages::preview::{pattern_preview_page, preview_page, Preview};
+use crate::patterns::{match_pattern, CompiledPattern, PatternKind};
+use crate::balance::{next_url, recent_counts};
+use crate::destinations::{pick_url, request_host, PickContext};
+use crate::health::failover_candidates;
+use crate::schedule::ScheduleRule;
+use crate::storage::CorruptMap;
+use crate::resolve::{describe_expansion, expand_url, ExpandError, merge_query, PATH_SEGMENT, QUERY_VALUE};
+use crate::suggest::{only_strong_match, suggest, Suggestion};
 
-/// Builds HTML table rows of shortcuts, grouped by URL, with inline delete buttons.
-fn grouped_shortcuts_table_with_delete(shortcuts: &HashMap<String, String>) -> String {
-    let mut grouped: HashMap<&str, Vec<&str>> = HashMap::new();
-    for (key, url) in shortcuts.iter() {
-        grouped.entry(url.as_str()).or_default().push(key.as_str());
+/// Small button that opens the add modal pre-filled with this shortcut's
+/// settings, saving back to the collection it came from.
+fn edit_button(key: &str, collection: Collection, shortcut: &Shortcut) -> String {
+    let query_precedence = match shortcut.query_precedence {
+        QueryPrecedence::Incoming => "incoming",
+        QueryPrecedence::Saved => "saved",
+    };
+    // Modes the modal can't show are flagged so saving the edit keeps them
+    let (extra_urls, keeps_destinations) = match &shortcut.destinations {
+        Some(Destinations::Tabs { also_open }) => (also_open.join("\n"), false),
+        Some(_) => (String::new(), true),
+        None => (String::new(), false),
+    };
+    format!(
+        r#"<button type="button" class="edit-button" title="Edit {key}" data-key="{key}" data-collection="{collection}" data-url="{url}" data-query-precedence="{query_precedence}" data-redirect-status="{status}" data-cache-max-age="{max_age}" data-extra-urls="{extra_urls}" data-keep-destinations="{keeps_destinations}" data-description="{description}" data-tags="{tags}" data-owner="{owner}" data-expires="{expires}" data-deprecated="{deprecated}" data-claims-host="{claims_host}" style="background: none; border: none; color: var(--link-color); padding: 0; cursor: pointer; margin: 0 0 0 5px; font-size: 10px; line-height: 1;">&#9998;</button>"#,
+        key = encode_minimal(key),
+        collection = collection.name(),
+        url = encode_minimal(&shortcut.url),
+        status = shortcut.redirect_status,
+        max_age = shortcut.cache_max_age.map(|s| s.to_string()).unwrap_or_default(),
+        extra_urls = encode_minimal(&extra_urls),
+        description = encode_minimal(shortcut.description.as_deref().unwrap_or("")),
+        tags = encode_minimal(&shortcut.tags.join(", ")),
+        owner = encode_minimal(shortcut.owner.as_deref().unwrap_or("")),
+        expires = shortcut.expires.map(|d| d.to_string()).unwrap_or_default(),
+        deprecated = encode_minimal(shortcut.deprecated.as_deref().unwrap_or("")),
+        claims_host = shortcut.claims_host
+    )
+}
+
+// "mon, tue 09:00–17:00" style summary of when a schedule rule applies
+fn describe_rule(rule: &ScheduleRule) -> String {
+    let days = if rule.days.is_empty() {
+        "Every day".to_string()
+    } else {
+        rule.days.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")
+    };
+    let time = |t: Option<NaiveTime>, default: &str| t.map(|t| t.format("%H:%M").to_string()).unwrap_or(default.to_string());
+    if rule.from.is_none() && rule.until.is_none() {
+        days
+    } else {
+        format!("{} {}–{}", days, time(rule.from, "00:00"), time(rule.until, "24:00"))
+    }
+}
+
+/// Describes where a shortcut goes, for the Destination URL column.
+fn render_destination(shortcut: &Shortcut, context: &PickContext) -> String {
+    match &shortcut.destinations {
+        None => encode_minimal(&shortcut.url),
+        Some(Destinations::Schedule { rules }) => {
+            let items = rules
+                .iter()
+                .map(|r| format!("<li>{} &rarr; {}</li>", encode_minimal(&describe_rule(r)), encode_minimal(&r.url)))
+                .collect::<String>();
+            format!(
+                "<strong>Now:</strong> {}<ul class=\"destinations\">{}<li>Otherwise &rarr; {}</li></ul>",
+                encode_minimal(pick_url(shortcut, context)),
+                items,
+                encode_minimal(&shortcut.url)
+            )
+        }
+        Some(Destinations::Network { routes }) => {
+            let items = routes
+                .iter()
+                .map(|r| format!("<li>{} &rarr; {}</li>", encode_minimal(&r.network), encode_minimal(&r.url)))
+                .collect::<String>();
+            let viewer = context.client_network().unwrap_or("no configured network");
+            format!(
+                "<strong>For you ({}):</strong> {}<ul class=\"destinations\">{}<li>Everywhere else &rarr; {}</li></ul>",
+                encode_minimal(viewer),
+                encode_minimal(pick_url(shortcut, context)),
+                items,
+                encode_minimal(&shortcut.url)
+            )
+        }
+        Some(Destinations::Rotation { starts, every_days, urls }) => {
+            let items = urls.iter().map(|u| format!("<li>{}</li>", encode_minimal(u))).collect::<String>();
+            format!(
+                "<strong>Now:</strong> {}<br>Rotates every {} day(s) from {}:<ol class=\"destinations\">{}</ol>",
+                encode_minimal(pick_url(shortcut, context)),
+                every_days,
+                starts,
+                items
+            )
+        }
+        Some(Destinations::Failover { .. }) => {
+            let items = {
+                let health = context.health.lock().unwrap();
+                failover_candidates(shortcut)
+                    .into_iter()
+                    .flatten()
+                    .map(|u| {
+                        let status = match health.get(u) {
+                            Some(true) => "up",
+                            Some(false) => "down",
+                            None => "not checked yet",
+                        };
+                        format!("<li>{} ({})</li>", encode_minimal(u), status)
+                    })
+                    .collect::<String>()
+            };
+            format!(
+                "<strong>Now:</strong> {}<br>Failover order:<ol class=\"destinations\">{}</ol>",
+                encode_minimal(pick_url(shortcut, context)),
+                items
+            )
+        }
+        Some(Destinations::Balance { urls }) => {
+            let (counts, total) = recent_counts(context.balance, urls);
+            let items = urls
+                .iter()
+                .zip(counts)
+                .map(|(u, count)| {
+                    let share = match (count * 100).checked_div(total) {
+                        Some(percent) => format!("{}% of the last {}", percent, total),
+                        None => "no redirects yet".to_string(),
+                    };
+                    format!("<li>{} (weight {}, {})</li>", encode_minimal(&u.url), u.weight, share)
+                })
+                .collect::<String>();
+            format!("<strong>Balanced across:</strong><ul class=\"destinations\">{}</ul>", items)
+        }
+        Some(Destinations::Tabs { also_open }) => {
+            let items = std::iter::once(&shortcut.url)
+                .chain(also_open)
+                .map(|u| format!("<li>{}</li>", encode_minimal(u)))
+                .collect::<String>();
+            format!("<strong>Opens {} tabs:</strong><ol>{}</ol>", also_open.len() + 1, items)
+        }
+    }
+}
+
+/// Description, tags, owner and dates shown under a key in the table.
+fn render_key_details(shortcut: &Shortcut) -> String {
+    let mut details = Vec::new();
+    if let Some(description) = &shortcut.description {
+        details.push(encode_minimal(description));
+    }
+    if !shortcut.tags.is_empty() {
+        let tags = shortcut
+            .tags
+            .iter()
+            .map(|t| format!("<span class=\"tag\">{}</span>", encode_minimal(t)))
+            .collect::<String>();
+        details.push(tags);
+    }
+    if let Some(owner) = &shortcut.owner {
+        details.push(format!("owner: {}", encode_minimal(owner)));
+    }
+    let date = |d: &DateTime<Utc>| d.format("%Y-%m-%d").to_string();
+    match (&shortcut.created_at, &shortcut.updated_at) {
+        (Some(created), Some(updated)) if date(created) != date(updated) => {
+            details.push(format!("added {}, updated {}", date(created), date(updated)))
+        }
+        (Some(created), _) => details.push(format!("added {}", date(created))),
+        (None, Some(updated)) => details.push(format!("updated {}", date(updated))),
+        (None, None) => {}
+    }
+
+    if details.is_empty() {
+        return String::new();
+    }
+    format!("<div class=\"key-details\">{}</div>", details.join(" &middot; "))
+}
+
+/// Builds HTML table rows of shortcuts, grouped by URL, with inline edit and delete buttons.
+fn grouped_shortcuts_table_with_delete(shortcuts: &HashMap<String, (Collection, Shortcut)>, context: &PickContext) -> String {
+    let mut grouped: HashMap<String, Vec<&str>> = HashMap::new();
+    for (key, (_, shortcut)) in shortcuts.iter() {
+        grouped.entry(render_destination(shortcut, context)).or_default().push(key.as_str());
     }
 
     let mut rows = String::new();
     let mut grouped_vec: Vec<_> = grouped.into_iter().collect();
-    grouped_vec.sort_by_key(|(url, _)| url.to_owned());
+    grouped_vec.sort_by(|(a, _), (b, _)| a.cmp(b));
 
-    for (url, mut keys) in grouped_vec {
+    for (destination, mut keys) in grouped_vec {
         keys.sort();
         let key_links = keys
             .iter()
             .map(|k| {
+                let (collection, shortcut) = &shortcuts[*k];
                 let delete_form = format!(
                     r#"
                     <form action="/delete_shortcut" method="POST" style="display:inline; margin-left: 5px;" onsubmit="return confirm('Are you sure you want to delete shortcut: {}?');">
                         <input type="hidden" name="key" value="{}">
+                        <input type="hidden" name="collection" value="{}">
                         <button type="submit" class="delete-button" title="Delete {}" style="background: none; border: none; color: #ff6347; padding: 0; cursor: pointer; margin: 0; font-size: 10px; line-height: 1;">X</button>
                     </form>
                     "#,
                     encode_minimal(k),
                     encode_minimal(k),
+                    collection.name(),
                     encode_minimal(k)
                 );
 
-                format!("<span style='white-space: nowrap;'><a href=\"/{0}\">{0}</a>{1}</span>", encode_minimal(k), delete_form)
+                format!(
+                    "<span style='white-space: nowrap;'><a href=\"/{0}\">{0}</a>{1}{2}</span>{3}",
+                    encode_minimal(k),
+                    edit_button(k, *collection, shortcut),
+                    delete_form,
+                    render_key_details(shortcut)
+                )
             })
             .collect::<Vec<_>>()
             .join(" , ");
@@ -43,14 +240,14 @@ fn grouped_shortcuts_table_with_delete(shortcuts: &HashMap<String, String>) -> S
         rows.push_str(&format!(
             "<tr><td class=\"keys\">{}</td><td class=\"url\">{}</td></tr>",
             key_links,
-            encode_minimal(url)
+            destination
         ));
     }
     rows
 }
 
-pub fn render_shortcuts_table(shortcuts: &HashMap<String, String>) -> String {
-    let rows = grouped_shortcuts_table_with_delete(shortcuts);
+pub fn render_shortcuts_table(shortcuts: &HashMap<String, (Collection, Shortcut)>, context: &PickContext) -> String {
+    let rows = grouped_shortcuts_table_with_delete(shortcuts, context);
     format!(
         r#"
     <table class="grid">
@@ -66,12 +263,204 @@ pub fn render_shortcuts_table(shortcuts: &HashMap<String, String>) -> String {
     )
 }
 
+/// Lists wildcard and regex shortcuts in the order they're tried.
+pub fn render_patterns_table(patterns: &[CompiledPattern]) -> String {
+    if patterns.is_empty() {
+        return String::new();
+    }
+
+    let rows = patterns
+        .iter()
+        .map(|p| {
+            let kind = match p.shortcut.kind {
+                PatternKind::Wildcard => "Wildcard",
+                PatternKind::Regex => "Regex",
+            };
+            let invalid = if p.regex.is_none() { " <em>(invalid, never matches)</em>" } else { "" };
+            format!(
+                r#"<tr><td class="keys"><code>{pattern}</code>{invalid}
+                    <form action="/delete_pattern" method="POST" style="display:inline; margin-left: 5px;" onsubmit="return confirm('Are you sure you want to delete this pattern shortcut?');">
+                        <input type="hidden" name="pattern" value="{pattern}">
+                        <button type="submit" class="delete-button" title="Delete pattern" style="background: none; border: none; color: #ff6347; padding: 0; cursor: pointer; margin: 0; font-size: 10px; line-height: 1;">X</button>
+                    </form></td><td>{kind}</td><td>{priority}</td><td class="url">{url}</td></tr>"#,
+                pattern = encode_minimal(&p.shortcut.pattern),
+                priority = p.shortcut.priority,
+                url = encode_minimal(&p.shortcut.url)
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r#"
+    <h2>Pattern Shortcuts</h2>
+    <table class="grid">
+      <thead>
+        <tr><th>Pattern</th><th>Type</th><th>Priority</th><th>Destination URL</th></tr>
+      </thead>
+      <tbody>
+        {rows}
+      </tbody>
+    </table>
+    "#
+    )
+}
+
+// A highlighted box listing problems under a heading; nothing when there are none
+fn render_warnings(heading: &str, problems: &[String]) -> String {
+    if problems.is_empty() {
+        return String::new();
+    }
+
+    let items = problems
+        .iter()
+        .map(|p| format!("<li>{}</li>", encode_minimal(p)))
+        .collect::<String>();
+
+    format!(
+        r#"
+    <div class="warnings">
+      <h2>{heading}</h2>
+      <ul>{items}</ul>
+    </div>
+    "#
+    )
+}
+
+/// Warns about `go:` links in the saved data that can't be followed.
+pub fn render_chain_problems(problems: &[String]) -> String {
+    render_warnings("Broken Alias Chains", problems)
+}
+
+/// Lists aliases defined in more than one collection.
+pub fn render_collisions(collisions: &[String]) -> String {
+    render_warnings("Aliases in More Than One Collection", collisions)
+}
+
+/// Says which stored data failed to parse, where, and that it's read-only
+/// until fixed.
+pub fn render_corrupt(corrupt: &CorruptMap) -> String {
+    let mut problems: Vec<String> = corrupt
+        .iter()
+        .map(|(item, found)| {
+            let mut problem = format!(
+                "Saving to {} is disabled: {}. Fix the file and restart, or restore a backup.",
+                item, found
+            );
+            if let Some(copy) = &found.quarantined {
+                problem.push_str(&format!(" A copy of the unreadable file is kept as {}.", copy));
+            }
+            problem
+        })
+        .collect();
+    problems.sort();
+    render_warnings("Unreadable Data", &problems)
+}
+
+/// Shows a warning about something a form just saved, such as a new alias
+/// shadowing another.
+pub fn render_notice(notice: Option<&str>) -> String {
+    render_warnings("Heads Up", &notice.map(str::to_string).into_iter().collect::<Vec<_>>())
+}
+
+/// Lists shortcuts that have expired or will within `soon_days`, soonest first.
+pub fn render_expiring(shortcuts: &HashMap<String, (Collection, Shortcut)>, today: NaiveDate, soon_days: i64) -> String {
+    let mut expiring: Vec<(&String, &(Collection, Shortcut), NaiveDate)> = shortcuts
+        .iter()
+        .filter_map(|(key, entry)| entry.1.expires.map(|expires| (key, entry, expires)))
+        .filter(|(_, _, expires)| (*expires - today).num_days() <= soon_days)
+        .collect();
+    if expiring.is_empty() {
+        return String::new();
+    }
+    expiring.sort_by(|a, b| a.2.cmp(&b.2).then(a.0.cmp(b.0)));
+
+    let items = expiring
+        .iter()
+        .map(|(key, (collection, shortcut), expires)| {
+            let when = match (*expires - today).num_days() {
+                days if days <= 0 => format!("expired {}", expires),
+                1 => format!("expires tomorrow ({})", expires),
+                days => format!("expires in {} days ({})", days, expires),
+            };
+            let owner = shortcut
+                .owner
+                .as_deref()
+                .map(|o| format!(" &ndash; owner: {}", encode_minimal(o)))
+                .unwrap_or_default();
+            format!(
+                "<li><a href=\"/{0}\">{0}</a> {1}{2}{3}</li>",
+                encode_minimal(key),
+                when,
+                owner,
+                edit_button(key, *collection, shortcut)
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r#"
+    <div class="warnings">
+      <h2>Expiring Soon</h2>
+      <ul>{items}</ul>
+    </div>
+    "#
+    )
+}
+
+/// Builds the "Did you mean" box shown above the table. `retry_suffix` is
+/// whatever followed the mistyped alias, so suggestions keep the rest of the request.
+fn render_suggestions(suggestions: &[Suggestion], retry_suffix: &str) -> String {
+    if suggestions.is_empty() {
+        return String::new();
+    }
+
+    let href = |alias: &str| {
+        encode_minimal(&format!("/{}{}", utf8_percent_encode(alias, PATH_SEGMENT), retry_suffix))
+    };
+
+    let one_click = only_strong_match(suggestions)
+        .map(|s| {
+            format!(
+                r#"<p><a class="suggestion-go" href="{}" autofocus>Go to {} &rarr;</a></p>"#,
+                href(&s.alias),
+                encode_minimal(&s.alias)
+            )
+        })
+        .unwrap_or_default();
+
+    let items = suggestions
+        .iter()
+        .map(|s| {
+            format!(
+                "<li><a href=\"{}\">{}</a> &ndash; <span class=\"url\">{}</span></li>",
+                href(&s.alias),
+                encode_minimal(&s.alias),
+                encode_minimal(&s.url)
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r#"
+    <div class="suggestions">
+      <h2>Did you mean?</h2>
+      {one_click}
+      <ul>{items}</ul>
+    </div>
+    "#
+    )
+}
+
 pub fn not_found_page(
-    shortcuts: &HashMap<String, String>,
+    shortcuts: &HashMap<String, (Collection, Shortcut)>,
+    context: &PickContext,
+    patterns: &[CompiledPattern],
+    suggestions: &[Suggestion],
+    retry_suffix: &str,
     current_theme: &Theme,
     saved_themes: &HashMap<String, Theme>
 ) -> String {
-    let table = render_shortcuts_table(shortcuts);
+    let table = format!("{}{}", render_shortcuts_table(shortcuts, context), render_patterns_table(patterns));
 
     let nav_with_button = nav_bar_html()
         .replace(r#"<div id="optional-button-placeholder"></div>"#, &render_add_shortcut_button());
@@ -79,9 +468,11 @@ pub fn not_found_page(
     let content = format!(
         r#"
     <h1>404 – Shortcut Not Found</h1>
+    {}
     <p>The requested shortcut was not found. Here are your available shortcuts:</p>
     {}
     "#,
+        render_suggestions(suggestions, retry_suffix),
         table
     );
 
@@ -90,51 +481,422 @@ pub fn not_found_page(
         .replace("</body>", &format!("{}</body>", render_add_shortcut_modal()))
 }
 
+/// Redirects with the shortcut's own status code and cache policy.
+fn redirect_response(shortcut: &Shortcut, location: String) -> HttpResponse {
+    let status = StatusCode::from_u16(shortcut.redirect_status).unwrap_or(StatusCode::FOUND);
+    let cache_control = match shortcut.cache_max_age {
+        Some(seconds) => format!("max-age={}", seconds),
+        None => "no-store".to_string(),
+    };
+
+    HttpResponse::build(status)
+        .append_header(("Location", location))
+        .append_header(("Cache-Control", cache_control))
+        .finish()
+}
+
 /// Catch‑all route for shortcuts
 #[get("/{tail:.*}")]
-pub async fn go(path: web::Path<String>, state: Data<Arc<AppState>>) -> impl Responder {
-    let req_path = path.into_inner();
+pub async fn go(req: HttpRequest, path: web::Path<String>, state: Data<Arc<AppState>>) -> impl Responder {
+    resolve(&req, path.into_inner(), req.query_string(), &state)
+}
+
+/// Answers a request for `raw_path` (everything after `go/`), with
+/// `incoming_query` as the query string to pass on. Shared by `go` and the
+/// `?q=` form of the home page.
+pub fn resolve(req: &HttpRequest, raw_path: String, incoming_query: &str, state: &Arc<AppState>) -> HttpResponse {
+    let mut click = None;
+    let response = respond(req, raw_path, incoming_query, state, &mut click);
+
+    // Usage stats, for backends that keep them. Recorded once `respond` has let
+    // go of every lock, on the blocking pool, so a slow write holds up nothing;
+    // failing to record never blocks the redirect
+    if let Some((collection, alias)) = click
+        && state.storage.records_clicks()
+    {
+        let state = Arc::clone(state);
+        let at = Utc::now();
+        actix_web::rt::task::spawn_blocking(move || {
+            if let Err(e) = state.storage.record_click(collection, &alias, at) {
+                eprintln!("Failed to record a click on {}: {}", alias, e);
+            }
+        });
+    }
+    response
+}
+
+// Builds the response for `resolve`, setting `click` to the alias followed, if any
+fn respond(
+    req: &HttpRequest,
+    raw_path: String,
+    incoming_query: &str,
+    state: &AppState,
+    click: &mut Option<(Collection, String)>,
+) -> HttpResponse {
+    // A leading "!" skips the configured fallback so a miss always shows the table
+    let (mut skip_fallback, mut req_path) = match raw_path.strip_prefix('!') {
+        Some(rest) => (true, rest.to_string()),
+        None => (false, raw_path),
+    };
 
     let shortcuts = state.shortcuts.lock().unwrap();
     let hidden_shortcuts = state.hidden_shortcuts.lock().unwrap();
     let work_shortcuts = state.work_shortcuts.lock().unwrap();
+    let pattern_shortcuts = state.pattern_shortcuts.lock().unwrap();
     let current_theme = state.current_theme.lock().unwrap();
     let saved_themes = state.saved_themes.lock().unwrap();
 
-    let find_url = |key: &str| -> Option<String> {
-        shortcuts.get(key)
-            .or_else(|| hidden_shortcuts.get(key))
-            .or_else(|| work_shortcuts.get(key))
-            .cloned()
+    let order = state.config.collections_for(request_host(req));
+    let arranged = arrange(&order, &shortcuts, &hidden_shortcuts, &work_shortcuts);
+    let collections = maps(&arranged);
+    let policy = &state.config.normalization;
+
+    // `?preview`, or `+` after a bare alias, shows where it goes instead of going there.
+    // The flag itself isn't passed on to the destination.
+    let is_preview_flag = |pair: &&str| *pair == "preview" || pair.starts_with("preview=");
+    let mut preview = incoming_query.split('&').any(|p| is_preview_flag(&p));
+    let query = incoming_query
+        .split('&')
+        .filter(|p| !is_preview_flag(p))
+        .collect::<Vec<_>>()
+        .join("&");
+    if req_path.len() > 1
+        && req_path.ends_with('+')
+        && !req_path.contains(['/', ' '])
+        && lookup(&collections, &req_path, policy).is_none()
+    {
+        req_path.pop();
+        preview = true;
+    }
+
+    if preview {
+        // Same order as a real visit: exact alias, then patterns, then alias + remainder
+        let exact = lookup_indexed(&collections, &req_path, policy).map(|m| (m, ""));
+        if exact.is_none()
+            && let Some((pattern, url)) = match_pattern(&pattern_shortcuts, &req_path)
+        {
+            let url = merge_query(&url, &query, QueryPrecedence::default());
+            return HttpResponse::Ok()
+                .content_type("text/html; charset=utf-8")
+                .append_header(("Cache-Control", "no-store"))
+                .body(pattern_preview_page(&req_path, pattern, &url, &current_theme, &saved_themes));
+        }
+        let matched = exact.or_else(|| {
+            let (alias, remainder) = req_path.split_once(['/', ' '])?;
+            lookup_indexed(&collections, alias, policy).map(|m| (m, remainder))
+        });
+
+        if let Some(((index, key, start), remainder)) = matched {
+            let body = match chain_steps(&collections, key, start, policy) {
+                Err(e) => error_page("Broken Alias Chain", &e.to_string(), &current_theme, &saved_themes),
+                Ok(hops) => {
+                    let shortcut = hops.last().map_or(start, |(_, _, s)| s);
+                    let context = PickContext::new(req, state);
+                    let saved_url = pick_url(shortcut, &context);
+                    // Hidden aliases are only listed when the one previewed is hidden too
+                    let synonyms = synonyms(&collections, shortcut, policy)
+                        .into_iter()
+                        .filter(|(i, k)| {
+                            *k != key
+                                && (arranged[*i].0 != Collection::Hidden || arranged[index].0 == Collection::Hidden)
+                        })
+                        .map(|(i, k)| (arranged[i].0.name(), k.as_str()))
+                        .collect();
+
+                    preview_page(
+                        &Preview {
+                            typed: &req_path,
+                            steps: std::iter::once((index, key))
+                                .chain(hops.iter().map(|(i, k, _)| (*i, *k)))
+                                .map(|(i, k)| (arranged[i].0.name(), k.as_str()))
+                                .collect(),
+                            shortcut,
+                            destinations_html: shortcut
+                                .destinations
+                                .is_some()
+                                .then(|| render_destination(shortcut, &context)),
+                            saved_url,
+                            expansion: describe_expansion(saved_url, remainder),
+                            query: &query,
+                            result: expand_url(saved_url, remainder)
+                                .map(|u| merge_query(&u, &query, shortcut.query_precedence))
+                                .map_err(|e| e.to_string()),
+                            synonyms,
+                        },
+                        &current_theme,
+                        &saved_themes,
+                    )
+                }
+            };
+            return HttpResponse::Ok()
+                .content_type("text/html; charset=utf-8")
+                .append_header(("Cache-Control", "no-store"))
+                .body(body);
+        }
+
+        // Nothing to preview: show the not-found page rather than any fallback redirect
+        skip_fallback = true;
+    }
+
+    // Finds a shortcut, following any `go:` chain through to the one with a real URL.
+    // The shortcut that was typed comes back too, for its expiry and deprecation,
+    // along with where it was found, for usage stats.
+    type Found = ((Collection, String), (Shortcut, Result<Shortcut, ChainError>));
+    let find_url = |key: &str| -> Option<Found> {
+        lookup_indexed(&collections, key, policy).map(|(index, found_key, shortcut)| {
+            (
+                (arranged[index].0, found_key.clone()),
+                (shortcut.clone(), follow_chain(&collections, found_key, shortcut, policy)),
+            )
+        })
+    };
+
+    // Expands the saved URL with whatever followed the alias, or explains why it couldn't.
+    let redirect = |(typed, found): (Shortcut, Result<Shortcut, ChainError>), alias: &str, remainder: &str| {
+        let shortcut = match found {
+            Ok(shortcut) => shortcut,
+            Err(e) => {
+                let mut response = match e {
+                    ChainError::Missing { .. } => HttpResponse::NotFound(),
+                    _ => HttpResponse::LoopDetected(),
+                };
+                return response
+                    .content_type("text/html; charset=utf-8")
+                    .body(error_page("Broken Alias Chain", &e.to_string(), &current_theme, &saved_themes));
+            }
+        };
+
+        let context = PickContext::new(req, state);
+        let today = context.now.date();
+        if let Some(expired) = [&typed, &shortcut].into_iter().find(|s| s.is_expired(today)) {
+            return HttpResponse::Gone()
+                .content_type("text/html; charset=utf-8")
+                .append_header(("Cache-Control", "no-store"))
+                .body(expired_page(alias, expired, &current_theme, &saved_themes));
+        }
+        let deprecated = typed.deprecated.as_deref().or(shortcut.deprecated.as_deref());
+
+        let expand = |url: &str| {
+            expand_url(url, remainder).map(|u| merge_query(&u, &query, shortcut.query_precedence))
+        };
+        let missing_arguments = |e: ExpandError| {
+            HttpResponse::BadRequest()
+                .content_type("text/html; charset=utf-8")
+                .body(error_page("Missing Shortcut Arguments", &e.to_string(), &current_theme, &saved_themes))
+        };
+
+        match &shortcut.destinations {
+            Some(Destinations::Tabs { also_open }) => {
+                let urls: Result<Vec<String>, ExpandError> = std::iter::once(&shortcut.url)
+                    .chain(also_open)
+                    .map(|u| expand(u))
+                    .collect();
+                match urls {
+                    Ok(urls) => HttpResponse::Ok()
+                        .content_type("text/html; charset=utf-8")
+                        .append_header(("Cache-Control", "no-store"))
+                        .body(launch_page(alias, &urls, &current_theme, &saved_themes)),
+                    Err(e) => missing_arguments(e),
+                }
+            }
+            _ => {
+                let picked = match &shortcut.destinations {
+                    Some(Destinations::Balance { urls }) => next_url(&state.balance, urls).unwrap_or(&shortcut.url),
+                    _ => pick_url(&shortcut, &context),
+                };
+                match expand(picked) {
+                    Ok(new_url) => {
+                        if let Some(replacement) = deprecated {
+                            return HttpResponse::Ok()
+                                .content_type("text/html; charset=utf-8")
+                                .append_header(("Cache-Control", "no-store"))
+                                .body(deprecated_page(
+                                    alias,
+                                    replacement,
+                                    &new_url,
+                                    state.config.lifecycle.deprecation_countdown_secs,
+                                    &current_theme,
+                                    &saved_themes,
+                                ));
+                        }
+
+                        let mut response = redirect_response(&shortcut, new_url);
+                        // Say which failover destination was used, e.g. "2/3 http://backup:8096"
+                        if let Some(candidates) = failover_candidates(&shortcut)
+                            && let Some(position) = candidates.iter().position(|u| *u == picked)
+                            && let Ok(value) =
+                                HeaderValue::from_str(&format!("{}/{} {}", position + 1, candidates.len(), picked))
+                        {
+                            response.headers_mut().insert(HeaderName::from_static("x-go-failover"), value);
+                        }
+                        response
+                    }
+                    Err(e) => missing_arguments(e),
+                }
+            }
+        }
     };
 
     // 1. Exact Match
-    if let Some(url) = find_url(&req_path) {
+    if let Some((followed, found)) = find_url(&req_path) {
+        *click = Some(followed);
+        return redirect(found, &req_path, "");
+    }
+
+    // 2. Patterns, in priority order, against the whole path. They come before
+    //    alias + remainder so a "docs/*" pattern isn't shadowed by a plain "docs" alias.
+    if let Some((_, url)) = match_pattern(&pattern_shortcuts, &req_path) {
         return HttpResponse::Found()
-            .append_header(("Location", url))
+            .append_header(("Location", merge_query(&url, &query, QueryPrecedence::default())))
             .finish();
     }
 
-    // 2. Smart Append: "youtube/omegagiven" -> alias "youtube" + remainder "omegagiven"
-    if let Some((alias, remainder)) = req_path.split_once('/') {
-        if let Some(base_url) = find_url(alias) {
-            let new_url = if base_url.ends_with('/') {
-                format!("{}{}", base_url, remainder)
-            } else {
-                format!("{}/{}", base_url, remainder)
-            };
+    // 3. Smart Append / Template: "youtube/omegagiven" -> alias "youtube" + remainder "omegagiven".
+    //    A space also ends the alias so URL-bar searches like "g rust async" reach keyword shortcuts.
+    if let Some((alias, remainder)) = req_path.split_once(['/', ' '])
+        && let Some((followed, found)) = find_url(alias)
+    {
+        *click = Some(followed);
+        return redirect(found, alias, remainder);
+    }
 
+    // 4. Fallback for anything else that was typed
+    if !skip_fallback && !req_path.is_empty() {
+        let fallback = &state.config.fallback;
+        let target = match fallback.mode {
+            FallbackMode::Table => None,
+            FallbackMode::Search => expand_url(&fallback.search_url, &req_path).ok(),
+            FallbackMode::Suggest => Some(format!("/_search?q={}", utf8_percent_encode(&req_path, QUERY_VALUE))),
+        };
+        if let Some(url) = target {
             return HttpResponse::Found()
-                .append_header(("Location", new_url))
+                .append_header(("Location", url))
                 .finish();
         }
     }
 
-    // 3. Not Found
-    let mut combined_shortcuts = shortcuts.clone();
-    combined_shortcuts.extend(work_shortcuts.clone());
+    // 5. Not Found: suggest aliases that look like the one typed
+    let typed_len = req_path.find(['/', ' ']).unwrap_or(req_path.len());
+    let (typed, rest) = req_path.split_at(typed_len);
+    let mut retry_suffix = utf8_percent_encode(rest, PATH_SEGMENT).to_string();
+    if !query.is_empty() {
+        retry_suffix = format!("{}?{}", retry_suffix, query);
+    }
+
+    let suggestion_config = &state.config.suggestions;
+    let searched = searchable(&arranged, suggestion_config.include_hidden);
+    let suggestions = suggest(typed, &searched, suggestion_config.limit);
+
+    let combined_shortcuts = merge_visible(&arranged);
 
     HttpResponse::NotFound()
         .content_type("text/html; charset=utf-8")
-        .body(not_found_page(&combined_shortcuts, &current_theme, &saved_themes))
+        .body(not_found_page(&combined_shortcuts, &PickContext::new(req, state), &pattern_shortcuts, &suggestions, &retry_suffix, &current_theme, &saved_themes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App};
+
+    use crate::config::Config;
+    use crate::patterns::PatternShortcut;
+
+    // Fetches `uri` with `shortcuts` (as written in shortcuts.json) saved
+    async fn get(shortcuts: &str, uri: &str) -> (StatusCode, String) {
+        let (status, _, body) = get_with_patterns(shortcuts, Vec::new(), uri).await;
+        (status, body)
+    }
+
+    // Same, with `patterns` saved too; also returns where a redirect points
+    async fn get_with_patterns(
+        shortcuts: &str,
+        patterns: Vec<PatternShortcut>,
+        uri: &str,
+    ) -> (StatusCode, Option<String>, String) {
+        let state = Arc::new(AppState::for_tests(Config::default()));
+        *state.shortcuts.lock().unwrap() = serde_json::from_str(shortcuts).unwrap();
+        *state.pattern_shortcuts.lock().unwrap() = patterns.into_iter().map(CompiledPattern::new).collect();
+        let app = test::init_service(App::new().app_data(Data::new(state)).service(go)).await;
+        let response = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        let status = response.status();
+        let location = response.headers().get("Location").map(|l| l.to_str().unwrap().to_string());
+        let body = test::read_body(response).await;
+        (status, location, String::from_utf8_lossy(&body).into_owned())
+    }
+
+    #[actix_web::test]
+    async fn current_shortcut_redirects() {
+        let shortcuts = r#"{"docs": {"url": "https://docs.example.com", "expires": "2999-01-01"}}"#;
+        let (status, _) = get(shortcuts, "/docs").await;
+        assert_eq!(status, StatusCode::FOUND);
+    }
+
+    #[actix_web::test]
+    async fn expired_shortcut_is_gone_and_names_its_owner() {
+        let shortcuts = r#"{"old": {"url": "https://old.example.com", "expires": "2000-01-01", "owner": "sam"}}"#;
+        let (status, body) = get(shortcuts, "/old").await;
+        assert_eq!(status, StatusCode::GONE);
+        assert!(body.contains("go/old is gone"));
+        assert!(body.contains("<strong>sam</strong>"));
+        assert!(!body.contains("https://old.example.com"));
+    }
+
+    #[actix_web::test]
+    async fn deprecated_shortcut_counts_down_to_its_destination() {
+        let shortcuts = r#"{"old": {"url": "https://new.example.com", "deprecated": "new"}}"#;
+        let (status, body) = get(shortcuts, "/old").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains(r#"<a href="/new">go/new</a>"#));
+        assert!(body.contains(r#"window.location.replace("https://new.example.com")"#));
+    }
+
+    #[actix_web::test]
+    async fn deprecated_shortcut_only_forwards_itself_to_web_urls() {
+        let shortcuts = r#"{"old": {"url": "javascript:alert(1)", "deprecated": "new"}}"#;
+        let (status, body) = get(shortcuts, "/old").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains(r#"<a href="/new">go/new</a>"#));
+        assert!(!body.contains("window.location.replace"));
+        assert!(body.contains(r#"<span id="destination" class="url">javascript:alert(1)</span>"#));
+        assert!(!body.contains(r#"href="javascript:"#));
+    }
+
+    #[actix_web::test]
+    async fn preview_links_only_to_web_urls() {
+        let shortcuts = r#"{"docs": {"url": "https://docs.example.com"}, "run": {"url": "javascript:alert(1)"}}"#;
+        let (_, body) = get(shortcuts, "/docs+").await;
+        assert!(body.contains(r#"<a href="https://docs.example.com" class="url">"#));
+        let (status, body) = get(shortcuts, "/run+").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains(r#"<span class="url">javascript:alert(1)</span>"#));
+        assert!(!body.contains(r#"href="javascript:"#));
+    }
+
+    #[actix_web::test]
+    async fn launch_page_lists_non_web_urls_as_text() {
+        let shortcuts = r#"{"all": {"url": "https://a.example.com", "destinations": {"mode": "tabs", "also_open": ["javascript:alert(1)"]}}}"#;
+        let (status, body) = get(shortcuts, "/all").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains(r#"<a href="https://a.example.com" target="_blank" rel="noopener">"#));
+        assert!(body.contains(r#"<li><span class="url">javascript:alert(1)</span></li>"#));
+        assert!(!body.contains(r#"href="javascript:"#));
+    }
+
+    #[actix_web::test]
+    async fn patterns_are_tried_before_alias_and_remainder() {
+        let shortcuts = r#"{"docs": {"url": "https://docs.example.com"}}"#;
+        let pattern = || PatternShortcut {
+            pattern: "docs/api-*".to_string(),
+            kind: PatternKind::Wildcard,
+            url: "https://api.example.com/${1}".to_string(),
+            priority: 0,
+        };
+        let (_, location, _) = get_with_patterns(shortcuts, vec![pattern()], "/docs/api-users").await;
+        assert_eq!(location.as_deref(), Some("https://api.example.com/users"));
+        let (_, location, _) = get_with_patterns(shortcuts, vec![pattern()], "/docs/guide").await;
+        assert_eq!(location.as_deref(), Some("https://docs.example.com/guide"));
+        let (_, _, body) = get_with_patterns(shortcuts, vec![pattern()], "/docs/api-users?preview").await;
+        assert!(body.contains("https://api.example.com/users"));
+    }
 }
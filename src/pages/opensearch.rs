@@ -0,0 +1,117 @@
+use actix_web::{get, web::{Data, Query}, HttpRequest, HttpResponse, Responder};
+use htmlescape::encode_minimal;
+use percent_encoding::utf8_percent_encode;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::app_state::AppState;
+use crate::destinations::request_host;
+use crate::lookup::{arrange, searchable};
+use crate::resolve::PATH_SEGMENT;
+use crate::suggest::suggest;
+
+// How many completions the browser is offered
+const SUGGEST_LIMIT: usize = 10;
+
+#[derive(Deserialize)]
+pub struct SuggestQuery {
+    pub q: Option<String>,
+}
+
+// "http://go" as the browser reached us, for the absolute URLs OpenSearch needs
+fn base_url(req: &HttpRequest) -> String {
+    let info = req.connection_info();
+    format!("{}://{}", info.scheme(), request_host(req).unwrap_or(info.host()))
+}
+
+/// OpenSearch description, so browsers can add go as a search engine with
+/// alias completions.
+#[get("/opensearch.xml")]
+pub async fn opensearch_description(req: HttpRequest) -> impl Responder {
+    let base = encode_minimal(&base_url(&req));
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/" xmlns:moz="http://www.mozilla.org/2006/browser/search/">
+  <ShortName>go</ShortName>
+  <Description>Jump to a saved go/ shortcut</Description>
+  <InputEncoding>UTF-8</InputEncoding>
+  <Image width="16" height="16" type="image/x-icon">{base}/favicon.ico</Image>
+  <Url type="text/html" method="get" template="{base}/?q={{searchTerms}}"/>
+  <Url type="application/x-suggestions+json" method="get" template="{base}/_suggest?q={{searchTerms}}"/>
+  <moz:SearchForm>{base}/_search</moz:SearchForm>
+</OpenSearchDescription>
+"#
+    );
+
+    HttpResponse::Ok()
+        .content_type("application/opensearchdescription+xml; charset=utf-8")
+        .body(xml)
+}
+
+/// Alias completions in the browser suggestion format:
+/// `[query, [completions], [descriptions], [urls]]`. Matches on the first
+/// word, by prefix first and then fuzzily, and keeps whatever followed it.
+#[get("/_suggest")]
+pub async fn suggestions(req: HttpRequest, query: Query<SuggestQuery>, state: Data<Arc<AppState>>) -> impl Responder {
+    let q = query.q.as_deref().unwrap_or("").trim_start();
+
+    let shortcuts = state.shortcuts.lock().unwrap();
+    let hidden_shortcuts = state.hidden_shortcuts.lock().unwrap();
+    let work_shortcuts = state.work_shortcuts.lock().unwrap();
+
+    let order = state.config.collections_for(request_host(&req));
+    let arranged = arrange(&order, &shortcuts, &hidden_shortcuts, &work_shortcuts);
+    let searched = searchable(&arranged, state.config.suggestions.include_hidden);
+
+    let alias_len = q.find(['/', ' ']).unwrap_or(q.len());
+    let (alias, rest) = q.split_at(alias_len);
+    let mut matches = suggest(alias, &searched, SUGGEST_LIMIT);
+    // Aliases that carry on from what was typed make the best completions
+    let typed = alias.to_lowercase();
+    matches.sort_by_key(|s| !s.alias.to_lowercase().starts_with(&typed));
+
+    let base = base_url(&req);
+    let completions: Vec<String> = matches.iter().map(|s| format!("{}{}", s.alias, rest)).collect();
+    let descriptions: Vec<&str> = matches.iter().map(|s| s.url.as_str()).collect();
+    let urls: Vec<String> = completions
+        .iter()
+        .map(|c| format!("{}/{}", base, utf8_percent_encode(c, PATH_SEGMENT)))
+        .collect();
+
+    HttpResponse::Ok()
+        .content_type("application/x-suggestions+json")
+        .json((q, completions, descriptions, urls))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App};
+    use serde_json::json;
+
+    use crate::config::Config;
+
+    #[actix_web::test]
+    async fn suggestions_complete_the_first_word_and_keep_the_rest() {
+        let state = Arc::new(AppState::for_tests(Config::default()));
+        *state.shortcuts.lock().unwrap() = serde_json::from_str(
+            r#"{"gitlab": "https://gitlab.com", "github": "https://github.com", "gut": "https://gut.example.com", "wiki": "https://wiki.example.com"}"#,
+        )
+        .unwrap();
+        *state.hidden_shortcuts.lock().unwrap() = serde_json::from_str(r#"{"gist": "https://gist.github.com"}"#).unwrap();
+
+        let app = test::init_service(App::new().app_data(Data::new(state)).service(suggestions)).await;
+        let req = test::TestRequest::get().uri("/_suggest?q=git%20rust").insert_header(("Host", "go")).to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(
+            body,
+            json!([
+                "git rust",
+                ["github rust", "gitlab rust", "gut rust"],
+                ["https://github.com", "https://gitlab.com", "https://gut.example.com"],
+                ["http://go/github%20rust", "http://go/gitlab%20rust", "http://go/gut%20rust"],
+            ])
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use htmlescape::encode_minimal;
+use percent_encoding::utf8_percent_encode;
+use std::collections::HashMap;
+
+use crate::app_state::{QueryPrecedence, Shortcut, Theme};
+use crate::base_page::render_base_page;
+use crate::patterns::{PatternKind, PatternShortcut};
+use crate::resolve::{is_web_url, PATH_SEGMENT};
+
+/// Everything the preview page shows about how an alias resolved.
+pub struct Preview<'a> {
+    pub typed: &'a str,                    // The request, without the preview marker
+    pub steps: Vec<(&'a str, &'a str)>,    // (collection, key) for the match and each `go:` hop
+    pub shortcut: &'a Shortcut,            // Where the chain ended
+    pub destinations_html: Option<String>, // The table's description, for multi-destination modes
+    pub saved_url: &'a str,                // The URL the remainder was applied to
+    pub expansion: Vec<String>,            // How the remainder was applied
+    pub query: &'a str,                    // Incoming query merged into the result
+    pub result: Result<String, String>,    // Final URL, or why there isn't one
+    pub synonyms: Vec<(&'a str, &'a str)>, // (collection, key) of other aliases reaching the same URL
+}
+
+fn alias_link(key: &str) -> String {
+    format!(
+        "<a href=\"/{}+\">{}</a>",
+        encode_minimal(&utf8_percent_encode(key, PATH_SEGMENT).to_string()),
+        encode_minimal(key)
+    )
+}
+
+// Only http(s) destinations are clickable; anything else is shown as text, as in the table
+fn destination(url: &str) -> String {
+    if is_web_url(url) {
+        format!("<a href=\"{0}\" class=\"url\">{0}</a>", encode_minimal(url))
+    } else {
+        format!("<span class=\"url\">{}</span>", encode_minimal(url))
+    }
+}
+
+fn row(label: &str, value: &str) -> String {
+    format!("<tr><th>{}</th><td>{}</td></tr>", label, value)
+}
+
+/// Shows where an alias would send someone, without sending them.
+pub fn preview_page(preview: &Preview, current_theme: &Theme, saved_themes: &HashMap<String, Theme>) -> String {
+    let shortcut = preview.shortcut;
+
+    let result = match &preview.result {
+        Ok(url) => destination(url),
+        Err(message) => format!("<em>{}</em>", encode_minimal(message)),
+    };
+
+    let matched = preview
+        .steps
+        .iter()
+        .map(|(collection, key)| format!("{} <small>({})</small>", alias_link(key), collection))
+        .collect::<Vec<_>>()
+        .join(" &rarr; ");
+
+    let expansion = preview
+        .expansion
+        .iter()
+        .map(|step| format!("<li>{}</li>", encode_minimal(step)))
+        .collect::<String>();
+
+    let query = if preview.query.is_empty() {
+        "None".to_string()
+    } else {
+        let winner = match shortcut.query_precedence {
+            QueryPrecedence::Incoming => "request values win",
+            QueryPrecedence::Saved => "saved values win",
+        };
+        format!("<code>{}</code> merged in ({})", encode_minimal(preview.query), winner)
+    };
+
+    let mut metadata = vec![
+        row("Redirect", &shortcut.redirect_status.to_string()),
+        row(
+            "Browser cache",
+            &shortcut.cache_max_age.map(|s| format!("{} seconds", s)).unwrap_or("Never".to_string()),
+        ),
+    ];
+    if let Some(owner) = &shortcut.owner {
+        metadata.push(row("Owner", &encode_minimal(owner)));
+    }
+    if let Some(expires) = shortcut.expires {
+        metadata.push(row("Expires", &expires.to_string()));
+    }
+    if let Some(replacement) = &shortcut.deprecated {
+        metadata.push(row("Deprecated", &format!("use {} instead", alias_link(replacement))));
+    }
+
+    let synonyms = if preview.synonyms.is_empty() {
+        "None".to_string()
+    } else {
+        preview
+            .synonyms
+            .iter()
+            .map(|(collection, key)| format!("{} <small>({})</small>", alias_link(key), collection))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let content = format!(
+        r#"
+    <h1>Preview: go/{typed}</h1>
+    <p><strong>Goes to:</strong> {result}</p>
+    <table class="grid">
+      <tbody>
+        {matched_row}
+        {destinations_row}
+        {saved_row}
+        <tr><th>Applied</th><td><ul>{expansion}</ul></td></tr>
+        {query_row}
+        {metadata}
+        {synonyms_row}
+      </tbody>
+    </table>
+    <p><a href="/">Back to all shortcuts</a></p>
+    "#,
+        typed = encode_minimal(preview.typed),
+        matched_row = row("Matched", &matched),
+        destinations_row = preview.destinations_html.as_deref().map(|d| row("Destinations", d)).unwrap_or_default(),
+        saved_row = row("Saved URL", &format!("<span class=\"url\">{}</span>", encode_minimal(preview.saved_url))),
+        query_row = row("Query", &query),
+        metadata = metadata.concat(),
+        synonyms_row = row("Synonyms", &synonyms),
+    );
+
+    render_base_page("Shortcut Preview", &content, current_theme, saved_themes)
+}
+
+/// Preview for a request that only a wildcard or regex shortcut matched.
+pub fn pattern_preview_page(
+    typed: &str,
+    pattern: &PatternShortcut,
+    url: &str,
+    current_theme: &Theme,
+    saved_themes: &HashMap<String, Theme>
+) -> String {
+    let kind = match pattern.kind {
+        PatternKind::Wildcard => "Wildcard",
+        PatternKind::Regex => "Regex",
+    };
+
+    let content = format!(
+        r#"
+    <h1>Preview: go/{typed}</h1>
+    <p><strong>Goes to:</strong> {url}</p>
+    <table class="grid">
+      <tbody>
+        {matched_row}
+        {saved_row}
+      </tbody>
+    </table>
+    <p><a href="/">Back to all shortcuts</a></p>
+    "#,
+        typed = encode_minimal(typed),
+        url = destination(url),
+        matched_row = row(
+            "Matched",
+            &format!(
+                "{} pattern <code>{}</code> <small>(priority {})</small>",
+                kind,
+                encode_minimal(&pattern.pattern),
+                pattern.priority
+            )
+        ),
+        saved_row = row("Saved URL", &format!("<span class=\"url\">{}</span>", encode_minimal(&pattern.url))),
+    );
+
+    render_base_page("Shortcut Preview", &content, current_theme, saved_themes)
+}
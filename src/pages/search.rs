@@ -0,0 +1,79 @@
+use actix_web::{get, web::{Data, Query}, HttpRequest, HttpResponse, Responder};
+use htmlescape::encode_minimal;
+use percent_encoding::utf8_percent_encode;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::app_state::AppState;
+use crate::destinations::request_host;
+use crate::lookup::{arrange, searchable};
+use crate::base_page::render_base_page;
+use crate::resolve::PATH_SEGMENT;
+use crate::suggest::suggest;
+
+// How many matches the search page lists
+const SEARCH_LIMIT: usize = 25;
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+}
+
+/// Fuzzy search over saved aliases. Also where misses land when the
+/// fallback mode is "suggest".
+#[get("/_search")]
+pub async fn search(req: HttpRequest, query: Query<SearchQuery>, state: Data<Arc<AppState>>) -> impl Responder {
+    let q = query.q.as_deref().unwrap_or("").trim();
+
+    let shortcuts = state.shortcuts.lock().unwrap();
+    let hidden_shortcuts = state.hidden_shortcuts.lock().unwrap();
+    let work_shortcuts = state.work_shortcuts.lock().unwrap();
+    let current_theme = state.current_theme.lock().unwrap();
+    let saved_themes = state.saved_themes.lock().unwrap();
+
+    let order = state.config.collections_for(request_host(&req));
+    let arranged = arrange(&order, &shortcuts, &hidden_shortcuts, &work_shortcuts);
+    let searched = searchable(&arranged, state.config.suggestions.include_hidden);
+
+    // Search on the first word, the way `go` reads the alias out of the path
+    let alias = q.split(['/', ' ']).next().unwrap_or("");
+    let results = suggest(alias, &searched, SEARCH_LIMIT);
+
+    let results_html = if q.is_empty() {
+        String::new()
+    } else if results.is_empty() {
+        format!("<p>No aliases resemble <code>{}</code>.</p>", encode_minimal(q))
+    } else {
+        let items = results
+            .iter()
+            .map(|s| {
+                format!(
+                    "<li><a href=\"/{}\">{}</a> &ndash; <span class=\"url\">{}</span></li>",
+                    encode_minimal(&utf8_percent_encode(&s.alias, PATH_SEGMENT).to_string()),
+                    encode_minimal(&s.alias),
+                    encode_minimal(&s.url)
+                )
+            })
+            .collect::<String>();
+        format!("<ul>{}</ul>", items)
+    };
+
+    let content = format!(
+        r#"
+    <h1>Search Shortcuts</h1>
+    <form action="/_search" method="GET">
+      <input type="text" name="q" value="{q}" placeholder="e.g., calendar" autofocus>
+      <button type="submit">Search</button>
+    </form>
+    <div class="suggestions">
+      {results_html}
+    </div>
+    <p><a href="/">Show all shortcuts</a></p>
+    "#,
+        q = encode_minimal(q)
+    );
+
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(render_base_page("Search Shortcuts", &content, &current_theme, &saved_themes))
+}
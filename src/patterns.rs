@@ -0,0 +1,118 @@
+// Wildcard and regex shortcuts, tried after every exact alias has missed.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternKind {
+    Wildcard, // `*` captures any text, `?` a single character
+    Regex,    // Full regular expression, anchored at both ends
+}
+
+// A pattern shortcut as stored in pattern-shortcuts.json. Lower `priority`
+// values are tried first; ties keep their order in the file.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PatternShortcut {
+    pub pattern: String,
+    pub kind: PatternKind,
+    pub url: String,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+// A loaded pattern with its compiled regex. `regex` is None when the pattern
+// failed to compile, so a hand-edited mistake is reported but never lost on
+// the next save.
+pub struct CompiledPattern {
+    pub shortcut: PatternShortcut,
+    pub regex: Option<Regex>,
+}
+
+/// Turns a wildcard into an equivalent regex source, one capture group per `*` or `?`.
+fn wildcard_to_regex(pattern: &str) -> String {
+    let mut source = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => source.push_str("(.*)"),
+            '?' => source.push_str("(.)"),
+            _ => source.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    source.push('$');
+    source
+}
+
+/// Compiles a pattern so it must match the whole request path.
+pub fn compile(shortcut: &PatternShortcut) -> Result<Regex, regex::Error> {
+    let source = match shortcut.kind {
+        PatternKind::Wildcard => wildcard_to_regex(&shortcut.pattern),
+        PatternKind::Regex => format!("^(?:{})$", shortcut.pattern),
+    };
+    Regex::new(&source)
+}
+
+impl CompiledPattern {
+    pub fn new(shortcut: PatternShortcut) -> Self {
+        let regex = compile(&shortcut).ok();
+        CompiledPattern { shortcut, regex }
+    }
+}
+
+/// Keeps patterns in the order they're tried: by priority, then insertion order.
+pub fn sort_by_priority(patterns: &mut [CompiledPattern]) {
+    patterns.sort_by_key(|p| p.shortcut.priority);
+}
+
+/// Returns the first pattern matching `path` and its destination, with `$1`,
+/// `${1}` or `${name}` in the URL replaced by the captured text.
+pub fn match_pattern<'a>(patterns: &'a [CompiledPattern], path: &str) -> Option<(&'a PatternShortcut, String)> {
+    patterns.iter().find_map(|p| {
+        let caps = p.regex.as_ref()?.captures(path)?;
+        let mut url = String::new();
+        caps.expand(&p.shortcut.url, &mut url);
+        Some((&p.shortcut, url))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(pattern: &str, kind: PatternKind, url: &str, priority: i32) -> CompiledPattern {
+        CompiledPattern::new(PatternShortcut { pattern: pattern.to_string(), kind, url: url.to_string(), priority })
+    }
+
+    #[test]
+    fn wildcard_captures_fill_the_url() {
+        let patterns = [pattern("ticket-*", PatternKind::Wildcard, "https://jira/browse/T-$1", 0)];
+        let (_, url) = match_pattern(&patterns, "ticket-42").unwrap();
+        assert_eq!(url, "https://jira/browse/T-42");
+        assert!(match_pattern(&patterns, "my-ticket-42").is_none());
+    }
+
+    #[test]
+    fn regex_is_anchored_and_supports_named_groups() {
+        let patterns = [pattern(r"pr-(?<n>\d+)", PatternKind::Regex, "https://git/pull/${n}", 0)];
+        assert_eq!(match_pattern(&patterns, "pr-7").unwrap().1, "https://git/pull/7");
+        assert!(match_pattern(&patterns, "pr-7x").is_none());
+    }
+
+    #[test]
+    fn lower_priority_is_tried_first() {
+        let mut patterns = vec![
+            pattern("*", PatternKind::Wildcard, "https://catch-all/$1", 10),
+            pattern("doc-*", PatternKind::Wildcard, "https://docs/$1", 0),
+        ];
+        sort_by_priority(&mut patterns);
+        assert_eq!(match_pattern(&patterns, "doc-api").unwrap().1, "https://docs/api");
+        assert_eq!(match_pattern(&patterns, "other").unwrap().1, "https://catch-all/other");
+    }
+
+    #[test]
+    fn invalid_pattern_never_matches() {
+        let patterns = [pattern("(", PatternKind::Regex, "https://x", 0)];
+        assert!(patterns[0].regex.is_none());
+        assert!(match_pattern(&patterns, "(").is_none());
+    }
+}
@@ -0,0 +1,305 @@
+// Turns a saved shortcut URL plus whatever followed the alias in the request
+// into the final destination URL.
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS, NON_ALPHANUMERIC};
+use std::fmt;
+use url::Url;
+
+use crate::app_state::QueryPrecedence;
+
+/// Raised when a templated URL needs more path segments than were supplied.
+#[derive(Debug)]
+pub enum ExpandError {
+    MissingArgument { needed: usize, given: usize },
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpandError::MissingArgument { needed, given } => write!(
+                f,
+                "This shortcut needs {} argument{} but {} {} supplied.",
+                needed,
+                if *needed == 1 { "" } else { "s" },
+                given,
+                if *given == 1 { "was" } else { "were" }
+            ),
+        }
+    }
+}
+
+// A single piece of a parsed URL template.
+enum Part<'a> {
+    Literal(&'a str),
+    Positional(usize), // {1}, {2}, ... (1-based)
+    Rest,              // {*}
+    Query,             // {q}
+}
+
+/// Whether `url` is http or https. Only these are handed to `window.open` or
+/// `location`; a `javascript:` URL there would run inside this service's pages.
+pub fn is_web_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|u| matches!(u.scheme(), "http" | "https"))
+}
+
+/// Characters that can't appear raw in a path built from an alias or remainder.
+pub const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ').add(b'"').add(b'#').add(b'%').add(b'<').add(b'>').add(b'?').add(b'`').add(b'{').add(b'}');
+
+// Everything except RFC 3986 unreserved characters gets escaped in `{q}`
+pub const QUERY_VALUE: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+// Splits a URL into literal text and placeholders. Braces that don't wrap a
+// positive number, `*` or `q` are kept as literal text.
+fn parse_template(url: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = url;
+
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|i| open + i) else {
+            break;
+        };
+
+        let inner = &rest[open + 1..close];
+        let placeholder = if inner == "*" {
+            Some(Part::Rest)
+        } else if inner == "q" {
+            Some(Part::Query)
+        } else {
+            inner.parse::<usize>().ok().filter(|n| *n > 0).map(Part::Positional)
+        };
+
+        match placeholder {
+            Some(part) => {
+                if open > 0 {
+                    parts.push(Part::Literal(&rest[..open]));
+                }
+                parts.push(part);
+            }
+            None => parts.push(Part::Literal(&rest[..=close])),
+        }
+        rest = &rest[close + 1..];
+    }
+
+    if !rest.is_empty() {
+        parts.push(Part::Literal(rest));
+    }
+    parts
+}
+
+/// Builds the destination for `base_url` given the text after the alias.
+///
+/// Templated URLs have `{1}`, `{2}`, ... replaced with the slash-separated
+/// segments of `remainder`, and `{*}` with every segment after the highest
+/// numbered placeholder, each percent-encoded as a path. Keyword shortcuts use `{q}`, which takes the whole
+/// remainder (spaces and slashes included) percent-encoded as a single query
+/// value. URLs without placeholders get the remainder appended as a path,
+/// which is how shortcuts have always behaved.
+pub fn expand_url(base_url: &str, remainder: &str) -> Result<String, ExpandError> {
+    let parts = parse_template(base_url);
+    let has_placeholders = parts.iter().any(|p| !matches!(p, Part::Literal(_)));
+
+    if !has_placeholders {
+        if remainder.is_empty() {
+            return Ok(base_url.to_string());
+        }
+        // Append onto the path so a saved `?query` or `#fragment` stays at the end
+        let (path, suffix) = base_url.split_at(base_url.find(['?', '#']).unwrap_or(base_url.len()));
+        return Ok(if path.ends_with('/') {
+            format!("{}{}{}", path, remainder, suffix)
+        } else {
+            format!("{}/{}{}", path, remainder, suffix)
+        });
+    }
+
+    let args: Vec<&str> = if remainder.is_empty() {
+        Vec::new()
+    } else {
+        remainder.split('/').collect()
+    };
+
+    let needed = parts
+        .iter()
+        .filter_map(|p| match p {
+            Part::Positional(n) => Some(*n),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+
+    if args.len() < needed {
+        return Err(ExpandError::MissingArgument { needed, given: args.len() });
+    }
+
+    let mut out = String::with_capacity(base_url.len() + remainder.len());
+    for part in parts {
+        match part {
+            Part::Literal(text) => out.push_str(text),
+            Part::Positional(n) => out.extend(utf8_percent_encode(args[n - 1], PATH_SEGMENT)),
+            Part::Rest => out.extend(utf8_percent_encode(&args[needed..].join("/"), PATH_SEGMENT)),
+            Part::Query => out.extend(utf8_percent_encode(remainder, QUERY_VALUE)),
+        }
+    }
+    Ok(out)
+}
+
+/// Explains, one line per step, how `expand_url` treats `remainder` for this
+/// URL. Used by the preview page.
+pub fn describe_expansion(base_url: &str, remainder: &str) -> Vec<String> {
+    let parts = parse_template(base_url);
+    if parts.iter().all(|p| matches!(p, Part::Literal(_))) {
+        return vec![if remainder.is_empty() {
+            "Nothing followed the alias, so the saved URL is used as is.".to_string()
+        } else {
+            format!("\"{}\" is appended to the saved URL's path.", remainder)
+        }];
+    }
+
+    let args: Vec<&str> = if remainder.is_empty() { Vec::new() } else { remainder.split('/').collect() };
+    let needed = parts
+        .iter()
+        .filter_map(|p| match p {
+            Part::Positional(n) => Some(*n),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+
+    let mut steps = Vec::new();
+    for part in &parts {
+        let step = match part {
+            Part::Literal(_) => continue,
+            Part::Positional(n) => match args.get(n - 1) {
+                Some(arg) => format!("{{{}}} is filled with \"{}\".", n, arg),
+                None => format!("{{{}}} has nothing to fill it.", n),
+            },
+            Part::Rest => format!("{{*}} takes the rest: \"{}\".", args.get(needed..).unwrap_or_default().join("/")),
+            Part::Query => format!("{{q}} takes everything after the alias, encoded: \"{}\".", remainder),
+        };
+        if !steps.contains(&step) {
+            steps.push(step);
+        }
+    }
+    steps
+}
+
+// Name of a raw `key=value` query pair, without decoding it
+fn query_key(pair: &str) -> &str {
+    pair.split_once('=').map_or(pair, |(k, _)| k)
+}
+
+/// Merges the incoming request's query string into `url`.
+///
+/// Pairs are copied through without re-encoding. A key present on both sides
+/// takes all of its values from whichever side `precedence` names; keys only
+/// the request has are appended after the saved ones. The saved URL's
+/// `#fragment` always stays at the very end.
+pub fn merge_query(url: &str, incoming: &str, precedence: QueryPrecedence) -> String {
+    if incoming.is_empty() {
+        return url.to_string();
+    }
+
+    let (without_fragment, fragment) = match url.find('#') {
+        Some(i) => url.split_at(i),
+        None => (url, ""),
+    };
+    let (base, saved) = without_fragment
+        .split_once('?')
+        .unwrap_or((without_fragment, ""));
+
+    let saved_pairs: Vec<&str> = saved.split('&').filter(|p| !p.is_empty()).collect();
+    let incoming_pairs: Vec<&str> = incoming.split('&').filter(|p| !p.is_empty()).collect();
+
+    let saved_has = |key: &str| saved_pairs.iter().any(|p| query_key(p) == key);
+    let incoming_has = |key: &str| incoming_pairs.iter().any(|p| query_key(p) == key);
+
+    let mut merged: Vec<&str> = Vec::with_capacity(saved_pairs.len() + incoming_pairs.len());
+    let mut emitted: Vec<&str> = Vec::new();
+
+    for pair in &saved_pairs {
+        let key = query_key(pair);
+        if precedence == QueryPrecedence::Incoming && incoming_has(key) {
+            // Swap in the request's values where the saved key used to be
+            if !emitted.contains(&key) {
+                emitted.push(key);
+                merged.extend(incoming_pairs.iter().filter(|p| query_key(p) == key));
+            }
+        } else {
+            merged.push(pair);
+        }
+    }
+
+    for pair in &incoming_pairs {
+        let key = query_key(pair);
+        if !saved_has(key) {
+            merged.push(pair);
+        }
+    }
+
+    if merged.is_empty() {
+        format!("{}{}", base, fragment)
+    } else {
+        format!("{}?{}{}", base, merged.join("&"), fragment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_url_appends_remainder_as_path() {
+        assert_eq!(expand_url("https://github.com", "").unwrap(), "https://github.com");
+        assert_eq!(expand_url("https://github.com", "rust-lang").unwrap(), "https://github.com/rust-lang");
+        assert_eq!(expand_url("https://github.com/", "rust-lang").unwrap(), "https://github.com/rust-lang");
+        assert_eq!(expand_url("https://example.com/a?x=1#top", "b").unwrap(), "https://example.com/a/b?x=1#top");
+    }
+
+    #[test]
+    fn positional_and_rest_placeholders() {
+        let url = "https://github.com/{1}/{2}/tree/{*}";
+        assert_eq!(
+            expand_url(url, "rust-lang/rust/master/src").unwrap(),
+            "https://github.com/rust-lang/rust/tree/master/src"
+        );
+        assert_eq!(expand_url(url, "a/b").unwrap(), "https://github.com/a/b/tree/");
+    }
+
+    #[test]
+    fn placeholder_values_are_percent_encoded() {
+        assert_eq!(
+            expand_url("https://example.com/{1}/{*}", "a b/c?d#e/f g").unwrap(),
+            "https://example.com/a%20b/c%3Fd%23e/f%20g"
+        );
+    }
+
+    #[test]
+    fn missing_positional_arguments_are_an_error() {
+        let Err(ExpandError::MissingArgument { needed, given }) = expand_url("https://x/{1}/{2}", "only") else {
+            panic!("expected a missing argument");
+        };
+        assert_eq!((needed, given), (2, 1));
+    }
+
+    #[test]
+    fn keyword_placeholder_takes_whole_remainder_as_query_value() {
+        assert_eq!(
+            expand_url("https://www.google.com/search?q={q}", "rust async/await & more").unwrap(),
+            "https://www.google.com/search?q=rust%20async%2Fawait%20%26%20more"
+        );
+    }
+
+    #[test]
+    fn merge_query_appends_incoming_pairs() {
+        assert_eq!(merge_query("https://x/a", "", QueryPrecedence::Incoming), "https://x/a");
+        assert_eq!(merge_query("https://x/a", "b=1", QueryPrecedence::Incoming), "https://x/a?b=1");
+        assert_eq!(merge_query("https://x/a?a=1#f", "b=2", QueryPrecedence::Incoming), "https://x/a?a=1&b=2#f");
+    }
+
+    #[test]
+    fn merge_query_precedence_decides_shared_keys() {
+        let url = "https://x/?tab=all&sort=new";
+        assert_eq!(merge_query(url, "sort=old&sort=top", QueryPrecedence::Incoming), "https://x/?tab=all&sort=old&sort=top");
+        assert_eq!(merge_query(url, "sort=old&page=2", QueryPrecedence::Saved), "https://x/?tab=all&sort=new&page=2");
+    }
+}
@@ -0,0 +1,121 @@
+// Time-based destinations: weekday/time windows and rotating rosters.
+
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+// One window of a schedule. Every condition left out matches, so a rule with
+// only `days` covers those whole days.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScheduleRule {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<Weekday>, // e.g. ["mon", "tue"]; empty means every day
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<NaiveTime>, // "09:00"; inclusive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<NaiveTime>, // "17:30"; exclusive. Earlier than `from` wraps past midnight
+    pub url: String,
+}
+
+impl ScheduleRule {
+    fn matches(&self, now: NaiveDateTime) -> bool {
+        if !self.days.is_empty() && !self.days.contains(&now.weekday()) {
+            return false;
+        }
+
+        let time = now.time();
+        match (self.from, self.until) {
+            (Some(from), Some(until)) if from > until => time >= from || time < until,
+            (from, until) => from.is_none_or(|f| time >= f) && until.is_none_or(|u| time < u),
+        }
+    }
+}
+
+/// URL of the first rule matching `now`, if any.
+pub fn scheduled_url(rules: &[ScheduleRule], now: NaiveDateTime) -> Option<&str> {
+    rules.iter().find(|r| r.matches(now)).map(|r| r.url.as_str())
+}
+
+/// Entry of `urls` on duty at `now` for a roster that starts with the first
+/// URL on `starts` and moves to the next one every `every_days` days.
+pub fn rotation_url(urls: &[String], starts: NaiveDate, every_days: u32, now: NaiveDateTime) -> Option<&str> {
+    if urls.is_empty() || every_days == 0 {
+        return None;
+    }
+    let elapsed = (now.date() - starts).num_days();
+    let turn = elapsed.div_euclid(i64::from(every_days)).rem_euclid(urls.len() as i64);
+    Some(urls[turn as usize].as_str())
+}
+
+/// Current wall-clock time in `timezone` (an IANA name such as
+/// "Europe/Berlin"), or in the server's local zone when none is configured.
+pub fn now_in(timezone: Option<&str>) -> NaiveDateTime {
+    match timezone.and_then(|tz| tz.parse::<Tz>().ok()) {
+        Some(tz) => Utc::now().with_timezone(&tz).naive_local(),
+        None => Local::now().naive_local(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::new(date.parse().unwrap(), time.parse().unwrap())
+    }
+
+    fn rule(days: &[Weekday], from: Option<&str>, until: Option<&str>) -> ScheduleRule {
+        ScheduleRule {
+            days: days.to_vec(),
+            from: from.map(|t| t.parse().unwrap()),
+            until: until.map(|t| t.parse().unwrap()),
+            url: "https://x".to_string(),
+        }
+    }
+
+    // 2026-10-19 is a Monday
+    #[test]
+    fn window_includes_from_and_excludes_until() {
+        let office = rule(&[Weekday::Mon, Weekday::Tue], Some("09:00:00"), Some("17:00:00"));
+        assert!(office.matches(at("2026-10-19", "09:00:00")));
+        assert!(office.matches(at("2026-10-20", "16:59:59")));
+        assert!(!office.matches(at("2026-10-19", "17:00:00")));
+        assert!(!office.matches(at("2026-10-19", "08:59:59")));
+        assert!(!office.matches(at("2026-10-21", "12:00:00")));
+    }
+
+    #[test]
+    fn window_ending_before_it_starts_wraps_past_midnight() {
+        let night = rule(&[], Some("22:00:00"), Some("06:00:00"));
+        assert!(night.matches(at("2026-10-19", "23:30:00")));
+        assert!(night.matches(at("2026-10-19", "05:59:00")));
+        assert!(!night.matches(at("2026-10-19", "12:00:00")));
+    }
+
+    #[test]
+    fn missing_conditions_always_match() {
+        assert!(rule(&[], None, None).matches(at("2026-10-24", "03:00:00")));
+        assert!(rule(&[Weekday::Sat], None, None).matches(at("2026-10-24", "03:00:00")));
+        assert!(rule(&[], None, Some("12:00:00")).matches(at("2026-10-24", "00:00:00")));
+    }
+
+    #[test]
+    fn rotation_moves_on_every_period_and_wraps() {
+        let urls = ["https://a".to_string(), "https://b".to_string(), "https://c".to_string()];
+        let starts: NaiveDate = "2026-10-01".parse().unwrap();
+        let on = |date: &str| rotation_url(&urls, starts, 7, at(date, "12:00:00"));
+        assert_eq!(on("2026-10-01"), Some("https://a"));
+        assert_eq!(on("2026-10-07"), Some("https://a"));
+        assert_eq!(on("2026-10-08"), Some("https://b"));
+        assert_eq!(on("2026-10-22"), Some("https://a"));
+        // Before the start date the roster runs backwards
+        assert_eq!(on("2026-09-30"), Some("https://c"));
+    }
+
+    #[test]
+    fn rotation_without_urls_or_period_has_no_one_on_duty() {
+        let starts: NaiveDate = "2026-10-01".parse().unwrap();
+        assert_eq!(rotation_url(&[], starts, 7, at("2026-10-01", "12:00:00")), None);
+        assert_eq!(rotation_url(&["https://a".to_string()], starts, 0, at("2026-10-01", "12:00:00")), None);
+    }
+}
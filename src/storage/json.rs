@@ -0,0 +1,330 @@
+// The default backend: one pretty-printed JSON file per item, in the
+// directory the service runs from. Files are replaced atomically, and the
+// versions they replace are kept as `<file>.1.bak` (newest), `<file>.2.bak`, ...
+
+use chrono::{DateTime, Local};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{Backup, Corrupt, Storage};
+use crate::app_state::{Shortcut, Theme};
+use crate::config::Collection;
+use crate::patterns::{sort_by_priority, CompiledPattern, PatternShortcut};
+
+const SHORTCUTS_FILE: &str = "shortcuts.json";
+const HIDDEN_SHORTCUTS_FILE: &str = "hidden-shortcuts.json";
+const WORK_SHORTCUTS_FILE: &str = "work-shortcuts.json";
+const PATTERN_SHORTCUTS_FILE: &str = "pattern-shortcuts.json";
+const THEMES_FILE: &str = "themes.json";
+const CURRENT_THEME_FILE: &str = "current_theme.json";
+
+const FILES: [&str; 6] = [
+    SHORTCUTS_FILE,
+    HIDDEN_SHORTCUTS_FILE,
+    WORK_SHORTCUTS_FILE,
+    PATTERN_SHORTCUTS_FILE,
+    THEMES_FILE,
+    CURRENT_THEME_FILE,
+];
+
+pub struct JsonStorage {
+    dir: PathBuf,
+    backups: usize, // Generations of each file to keep
+}
+
+impl JsonStorage {
+    pub fn new(dir: impl AsRef<Path>, backups: usize) -> Self {
+        JsonStorage { dir: dir.as_ref().to_path_buf(), backups }
+    }
+
+    fn collection_file(collection: Collection) -> &'static str {
+        match collection {
+            Collection::Shortcuts => SHORTCUTS_FILE,
+            Collection::Hidden => HIDDEN_SHORTCUTS_FILE,
+            Collection::Work => WORK_SHORTCUTS_FILE,
+        }
+    }
+
+    // Errors name the file, since the caller only knows what it asked for. A
+    // file that exists but can't be loaded comes back as a `Corrupt`; when its
+    // bytes could be read they're first copied to `<file>.corrupt`, safe from
+    // whatever is done to fix the original.
+    fn read<T: DeserializeOwned>(&self, file: &str) -> io::Result<T> {
+        let bytes = match fs::read(self.dir.join(file)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(io::Error::new(e.kind(), format!("{file}: {e}")));
+            }
+            Err(e) => return Err(corrupt(file, None, e.to_string(), None)),
+        };
+
+        let data = match std::str::from_utf8(&bytes) {
+            Ok(data) => data,
+            Err(e) => {
+                let valid = std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default();
+                let line = valid.matches('\n').count() + 1;
+                let column = valid.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+                let quarantined = self.quarantine(file, &bytes);
+                return Err(corrupt(file, Some((line, column)), "not valid UTF-8".to_string(), quarantined));
+            }
+        };
+
+        serde_json::from_str(data).map_err(|e| {
+            // serde_json ends its message with the location, which is kept separately
+            let message = e.to_string();
+            let message = message.rsplit_once(" at line ").map_or(message.as_str(), |(m, _)| m);
+            let quarantined = self.quarantine(file, &bytes);
+            corrupt(file, Some((e.line(), e.column())), message.to_string(), quarantined)
+        })
+    }
+
+    // Copies unreadable data aside, returning where it went
+    fn quarantine(&self, file: &str, bytes: &[u8]) -> Option<String> {
+        let quarantine = format!("{file}.corrupt");
+        match fs::write(self.dir.join(&quarantine), bytes) {
+            Ok(()) => Some(quarantine),
+            Err(e) => {
+                eprintln!("Failed to quarantine {file}: {e}");
+                None
+            }
+        }
+    }
+
+    fn write<T: Serialize + ?Sized>(&self, file: &str, value: &T) -> io::Result<()> {
+        let data = serde_json::to_string_pretty(value)?;
+        self.replace(file, &data)
+    }
+
+    fn backup_path(&self, file: &str, generation: usize) -> PathBuf {
+        self.dir.join(format!("{file}.{generation}.bak"))
+    }
+
+    // Swaps in new contents so a crash or full disk never leaves `file` half
+    // written: the data goes to a temporary file that is synced and then
+    // renamed over the old one. The old version first becomes backup 1,
+    // moving older backups up a generation and dropping the oldest.
+    fn replace(&self, file: &str, data: &str) -> io::Result<()> {
+        let path = self.dir.join(file);
+        if fs::read_to_string(&path).is_ok_and(|current| current == data) {
+            return Ok(());
+        }
+
+        let temp = self.dir.join(format!("{file}.tmp"));
+        let written = File::create(&temp).and_then(|mut out| {
+            out.write_all(data.as_bytes())?;
+            out.sync_all()
+        });
+        if let Err(e) = written {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+
+        if self.backups > 0 && path.exists() {
+            for generation in (1..self.backups).rev() {
+                let older = self.backup_path(file, generation);
+                if older.exists() {
+                    fs::rename(&older, self.backup_path(file, generation + 1))?;
+                }
+            }
+            // A hard link keeps the old version's modified time; copy where links aren't supported
+            let newest = self.backup_path(file, 1);
+            let _ = fs::remove_file(&newest);
+            fs::hard_link(&path, &newest).or_else(|_| fs::copy(&path, &newest).map(drop))?;
+        }
+
+        fs::rename(&temp, &path)?;
+        // Make the rename itself durable; directories can't be opened for this everywhere
+        if let Ok(dir) = File::open(&self.dir) {
+            let _ = dir.sync_all();
+        }
+        Ok(())
+    }
+
+    // Checks that `data` would load as `file` before it's restored
+    fn validate(file: &str, data: &str) -> serde_json::Result<()> {
+        match file {
+            PATTERN_SHORTCUTS_FILE => serde_json::from_str::<Vec<PatternShortcut>>(data).map(drop),
+            THEMES_FILE => serde_json::from_str::<HashMap<String, Theme>>(data).map(drop),
+            CURRENT_THEME_FILE => serde_json::from_str::<Theme>(data).map(drop),
+            _ => serde_json::from_str::<HashMap<String, Shortcut>>(data).map(drop),
+        }
+    }
+}
+
+fn corrupt(file: &str, location: Option<(usize, usize)>, message: String, quarantined: Option<String>) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        Corrupt { source: file.to_string(), location, message, quarantined },
+    )
+}
+
+impl Storage for JsonStorage {
+    fn load_shortcuts(&self, collection: Collection) -> io::Result<HashMap<String, Shortcut>> {
+        self.read(Self::collection_file(collection))
+    }
+
+    fn save_shortcuts(&self, collection: Collection, shortcuts: &HashMap<String, Shortcut>) -> io::Result<()> {
+        self.write(Self::collection_file(collection), shortcuts)
+    }
+
+    fn load_patterns(&self) -> io::Result<Vec<CompiledPattern>> {
+        let list: Vec<PatternShortcut> = self.read(PATTERN_SHORTCUTS_FILE)?;
+        let mut patterns: Vec<CompiledPattern> = list.into_iter().map(CompiledPattern::new).collect();
+        sort_by_priority(&mut patterns);
+        Ok(patterns)
+    }
+
+    // Written in priority order so the file reads the way patterns are tried
+    fn save_patterns(&self, patterns: &[CompiledPattern]) -> io::Result<()> {
+        let list: Vec<&PatternShortcut> = patterns.iter().map(|p| &p.shortcut).collect();
+        self.write(PATTERN_SHORTCUTS_FILE, &list)
+    }
+
+    fn load_themes(&self) -> io::Result<HashMap<String, Theme>> {
+        self.read(THEMES_FILE)
+    }
+
+    fn save_themes(&self, themes: &HashMap<String, Theme>) -> io::Result<()> {
+        self.write(THEMES_FILE, themes)
+    }
+
+    fn load_current_theme(&self) -> io::Result<Theme> {
+        self.read(CURRENT_THEME_FILE)
+    }
+
+    fn save_current_theme(&self, theme: &Theme) -> io::Result<()> {
+        self.write(CURRENT_THEME_FILE, theme)
+    }
+
+    fn list_backups(&self) -> io::Result<Vec<Backup>> {
+        let mut backups = Vec::new();
+        for file in FILES {
+            for generation in 1..=self.backups {
+                let Ok(metadata) = fs::metadata(self.backup_path(file, generation)) else {
+                    continue;
+                };
+                backups.push(Backup {
+                    name: file.to_string(),
+                    generation,
+                    saved: metadata.modified().ok().map(DateTime::<Local>::from),
+                    bytes: metadata.len(),
+                });
+            }
+        }
+        Ok(backups)
+    }
+
+    fn restore_backup(&self, name: &str, generation: usize) -> io::Result<()> {
+        let file = FILES
+            .into_iter()
+            .find(|f| *f == name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("no file called {name} is backed up")))?;
+        let data = fs::read_to_string(self.backup_path(file, generation))
+            .map_err(|e| io::Error::new(e.kind(), format!("{file} backup {generation}: {e}")))?;
+        Self::validate(file, &data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{file} backup {generation}: {e}")))?;
+        self.replace(file, &data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh directory per test, removed when dropped
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("go-json-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn read(&self, file: &str) -> Option<String> {
+            fs::read_to_string(self.0.join(file)).ok()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn replace_keeps_the_configured_number_of_backups() {
+        let dir = TempDir::new("replace");
+        let storage = JsonStorage::new(&dir.0, 2);
+        for version in ["1", "2", "3", "4"] {
+            storage.replace(SHORTCUTS_FILE, version).unwrap();
+        }
+
+        assert_eq!(dir.read(SHORTCUTS_FILE).as_deref(), Some("4"));
+        assert_eq!(dir.read("shortcuts.json.1.bak").as_deref(), Some("3"));
+        assert_eq!(dir.read("shortcuts.json.2.bak").as_deref(), Some("2"));
+        assert_eq!(dir.read("shortcuts.json.3.bak"), None);
+        assert_eq!(dir.read("shortcuts.json.tmp"), None);
+    }
+
+    #[test]
+    fn replace_with_the_same_contents_keeps_the_backups() {
+        let dir = TempDir::new("unchanged");
+        let storage = JsonStorage::new(&dir.0, 2);
+        storage.replace(THEMES_FILE, "{}").unwrap();
+        storage.replace(THEMES_FILE, "{}").unwrap();
+        assert_eq!(dir.read("themes.json.1.bak"), None);
+    }
+
+    #[test]
+    fn restore_backup_swaps_versions_so_it_can_be_undone() {
+        let dir = TempDir::new("restore");
+        let storage = JsonStorage::new(&dir.0, 3);
+        storage.replace(SHORTCUTS_FILE, r#"{"gh": "https://github.com"}"#).unwrap();
+        storage.replace(SHORTCUTS_FILE, "{}").unwrap();
+
+        storage.restore_backup(SHORTCUTS_FILE, 1).unwrap();
+        assert_eq!(storage.load_shortcuts(Collection::Shortcuts).unwrap()["gh"].url, "https://github.com");
+        assert_eq!(dir.read("shortcuts.json.1.bak").as_deref(), Some("{}"));
+
+        let listed: Vec<(String, usize)> = storage.list_backups().unwrap().into_iter().map(|b| (b.name, b.generation)).collect();
+        assert_eq!(listed, [(SHORTCUTS_FILE.to_string(), 1), (SHORTCUTS_FILE.to_string(), 2)]);
+    }
+
+    #[test]
+    fn restore_backup_refuses_unknown_files_and_bad_backups() {
+        let dir = TempDir::new("refuse");
+        let storage = JsonStorage::new(&dir.0, 3);
+        assert_eq!(storage.restore_backup("../secrets", 1).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(storage.restore_backup(THEMES_FILE, 1).unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        storage.replace(THEMES_FILE, "{}").unwrap();
+        fs::write(dir.0.join("themes.json.1.bak"), "not json").unwrap();
+        assert_eq!(storage.restore_backup(THEMES_FILE, 1).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(dir.read(THEMES_FILE).as_deref(), Some("{}"));
+    }
+
+    #[test]
+    fn unreadable_file_is_quarantined_with_its_location() {
+        let dir = TempDir::new("corrupt");
+        let storage = JsonStorage::new(&dir.0, 0);
+        fs::write(dir.0.join(SHORTCUTS_FILE), "{\n  \"gh\": ,,\n}").unwrap();
+
+        let Err(e) = storage.load_shortcuts(Collection::Shortcuts) else {
+            panic!("a corrupt file loaded");
+        };
+        let found = Corrupt::from_error(&e).unwrap();
+        assert_eq!(found.location, Some((2, 9)));
+        assert_eq!(found.quarantined.as_deref(), Some("shortcuts.json.corrupt"));
+        assert_eq!(dir.read("shortcuts.json.corrupt"), dir.read(SHORTCUTS_FILE));
+
+        assert!(storage.load_shortcuts(Collection::Work).is_err_and(|e| e.kind() == io::ErrorKind::NotFound));
+    }
+}
@@ -0,0 +1,67 @@
+// Keeps everything in memory and forgets it on restart. Handy for trying the
+// service out, or for exercising handlers without touching the disk.
+
+use std::{
+    collections::HashMap,
+    io,
+    sync::Mutex,
+};
+
+use super::Storage;
+use crate::app_state::{Shortcut, Theme};
+use crate::config::Collection;
+use crate::patterns::{sort_by_priority, CompiledPattern, PatternShortcut};
+
+#[derive(Default)]
+pub struct MemoryStorage {
+    shortcuts: Mutex<HashMap<Collection, HashMap<String, Shortcut>>>,
+    patterns: Mutex<Option<Vec<PatternShortcut>>>,
+    themes: Mutex<Option<HashMap<String, Theme>>>,
+    current_theme: Mutex<Option<Theme>>,
+}
+
+fn not_saved(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("no {what} saved yet"))
+}
+
+impl Storage for MemoryStorage {
+    fn load_shortcuts(&self, collection: Collection) -> io::Result<HashMap<String, Shortcut>> {
+        let stored = self.shortcuts.lock().unwrap();
+        stored.get(&collection).cloned().ok_or_else(|| not_saved(&format!("{} collection", collection.name())))
+    }
+
+    fn save_shortcuts(&self, collection: Collection, shortcuts: &HashMap<String, Shortcut>) -> io::Result<()> {
+        self.shortcuts.lock().unwrap().insert(collection, shortcuts.clone());
+        Ok(())
+    }
+
+    fn load_patterns(&self) -> io::Result<Vec<CompiledPattern>> {
+        let list = self.patterns.lock().unwrap().clone().ok_or_else(|| not_saved("pattern shortcuts"))?;
+        let mut patterns: Vec<CompiledPattern> = list.into_iter().map(CompiledPattern::new).collect();
+        sort_by_priority(&mut patterns);
+        Ok(patterns)
+    }
+
+    fn save_patterns(&self, patterns: &[CompiledPattern]) -> io::Result<()> {
+        *self.patterns.lock().unwrap() = Some(patterns.iter().map(|p| p.shortcut.clone()).collect());
+        Ok(())
+    }
+
+    fn load_themes(&self) -> io::Result<HashMap<String, Theme>> {
+        self.themes.lock().unwrap().clone().ok_or_else(|| not_saved("themes"))
+    }
+
+    fn save_themes(&self, themes: &HashMap<String, Theme>) -> io::Result<()> {
+        *self.themes.lock().unwrap() = Some(themes.clone());
+        Ok(())
+    }
+
+    fn load_current_theme(&self) -> io::Result<Theme> {
+        self.current_theme.lock().unwrap().clone().ok_or_else(|| not_saved("current theme"))
+    }
+
+    fn save_current_theme(&self, theme: &Theme) -> io::Result<()> {
+        *self.current_theme.lock().unwrap() = Some(theme.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+// Where shortcuts and themes live between restarts. Handlers only talk to the
+// `Storage` in `AppState`; which backend that is comes from config.json.
+
+mod json;
+mod memory;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+use chrono::{DateTime, Local, Utc};
+use std::{collections::HashMap, error::Error, fmt, io};
+
+use crate::app_state::{Shortcut, Theme};
+use crate::config::{Collection, StorageBackend, StorageConfig};
+use crate::patterns::CompiledPattern;
+
+pub use json::JsonStorage;
+pub use memory::MemoryStorage;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStorage;
+
+/// Each thing a `Storage` loads and saves as a whole.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stored {
+    Collection(Collection),
+    Patterns,
+    Themes,
+    CurrentTheme,
+}
+
+impl fmt::Display for Stored {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stored::Collection(collection) => write!(f, "the {} collection", collection.name()),
+            Stored::Patterns => write!(f, "pattern shortcuts"),
+            Stored::Themes => write!(f, "saved themes"),
+            Stored::CurrentTheme => write!(f, "the current theme"),
+        }
+    }
+}
+
+/// Stored data that is there but can't be loaded, carried inside the
+/// `io::Error` a load returns. Whatever fails this way stays read-only until
+/// it's fixed, so the next save can't replace it with an empty copy.
+#[derive(Debug, Clone)]
+pub struct Corrupt {
+    pub source: String,                     // e.g. "shortcuts.json"
+    pub location: Option<(usize, usize)>,   // Line and column where reading stopped, 1-based
+    pub message: String,
+    pub quarantined: Option<String>,        // A copy of the unreadable data, safe from any fix attempts
+}
+
+impl Corrupt {
+    /// The corruption behind a failed load, if that's why it failed.
+    pub fn from_error(e: &io::Error) -> Option<&Corrupt> {
+        e.get_ref()?.downcast_ref()
+    }
+}
+
+impl fmt::Display for Corrupt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => {
+                write!(f, "{} can't be read (line {}, column {}): {}", self.source, line, column, self.message)
+            }
+            None => write!(f, "{} can't be read: {}", self.source, self.message),
+        }
+    }
+}
+
+impl Error for Corrupt {}
+
+/// What failed to load because it was corrupt.
+pub type CorruptMap = HashMap<Stored, Corrupt>;
+
+/// Logs why `item` didn't load. Anything but "never saved" makes it
+/// read-only: data that exists but couldn't be read (bad JSON, bad bytes,
+/// permissions, a failing disk) must not be replaced by the empty stand-in.
+pub fn report_load_error(item: Stored, e: &io::Error, corrupt: &mut CorruptMap) {
+    if e.kind() == io::ErrorKind::NotFound {
+        eprintln!("Failed to load {item}: {e}");
+        return;
+    }
+
+    let found = Corrupt::from_error(e).cloned().unwrap_or_else(|| Corrupt {
+        source: item.to_string(),
+        location: None,
+        message: e.to_string(),
+        quarantined: None,
+    });
+    eprintln!("Failed to load {item}: {found}. It is read-only until fixed.");
+    if let Some(copy) = &found.quarantined {
+        eprintln!("A copy of the unreadable data was saved as {copy}.");
+    }
+    corrupt.insert(item, found);
+}
+
+/// An earlier version of something stored, kept by backends that make backups.
+pub struct Backup {
+    pub name: String,      // What it's a version of, e.g. "shortcuts.json"
+    pub generation: usize, // 1 is the most recent
+    pub saved: Option<DateTime<Local>>,
+    pub bytes: u64,
+}
+
+/// Loads and saves everything the service persists. Each save replaces what
+/// was stored for that item; a load of something never saved fails with
+/// `ErrorKind::NotFound`.
+pub trait Storage: Send + Sync {
+    fn load_shortcuts(&self, collection: Collection) -> io::Result<HashMap<String, Shortcut>>;
+    fn save_shortcuts(&self, collection: Collection, shortcuts: &HashMap<String, Shortcut>) -> io::Result<()>;
+
+    /// Pattern shortcuts, sorted by priority.
+    fn load_patterns(&self) -> io::Result<Vec<CompiledPattern>>;
+    fn save_patterns(&self, patterns: &[CompiledPattern]) -> io::Result<()>;
+
+    fn load_themes(&self) -> io::Result<HashMap<String, Theme>>;
+    fn save_themes(&self, themes: &HashMap<String, Theme>) -> io::Result<()>;
+
+    fn load_current_theme(&self) -> io::Result<Theme>;
+    fn save_current_theme(&self, theme: &Theme) -> io::Result<()>;
+
+    /// Whether `record_click` keeps anything. Callers skip recording when it
+    /// doesn't, rather than handing the backend work it throws away.
+    fn records_clicks(&self) -> bool {
+        false
+    }
+
+    /// Notes that `alias` was followed. Backends that don't keep usage
+    /// stats ignore it.
+    fn record_click(&self, _collection: Collection, _alias: &str, _at: DateTime<Utc>) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Backups available to `restore_backup`, grouped by name, newest first.
+    fn list_backups(&self) -> io::Result<Vec<Backup>> {
+        Err(no_backups())
+    }
+
+    /// Makes backup `generation` of `name` the current version again. The
+    /// version it replaces becomes a backup in turn, so a restore can be undone.
+    fn restore_backup(&self, _name: &str, _generation: usize) -> io::Result<()> {
+        Err(no_backups())
+    }
+}
+
+fn no_backups() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "this storage backend doesn't keep backups")
+}
+
+/// Opens the backend named in config.json.
+pub fn open(config: &StorageConfig) -> io::Result<Box<dyn Storage>> {
+    Ok(match config.backend {
+        StorageBackend::Json => Box::new(JsonStorage::new(".", config.backups)),
+        StorageBackend::Memory => Box::new(MemoryStorage::default()),
+        #[cfg(feature = "sqlite")]
+        StorageBackend::Sqlite => Box::new(SqliteStorage::open(&config.sqlite_path, &JsonStorage::new(".", 0))?),
+        #[cfg(not(feature = "sqlite"))]
+        StorageBackend::Sqlite => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "this build has no SQLite support; rebuild with `--features sqlite`",
+            ))
+        }
+    })
+}
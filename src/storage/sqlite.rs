@@ -0,0 +1,478 @@
+// SQLite backend, built with `--features sqlite`. Shortcuts, tags, patterns,
+// themes and click events each get a table; every save runs in a single
+// transaction. A new database is filled from the JSON files on first open.
+
+use std::{collections::HashMap, error::Error, io, sync::Mutex};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{Map, Value};
+
+use super::Storage;
+use crate::app_state::{Shortcut, Theme};
+use crate::config::Collection;
+use crate::patterns::{sort_by_priority, CompiledPattern, PatternKind, PatternShortcut};
+
+// Bumped, with a step added to `migrate`, whenever the tables change
+const SCHEMA_VERSION: i32 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE collections (
+    name TEXT PRIMARY KEY
+);
+INSERT INTO collections (name) VALUES ('shortcuts'), ('hidden'), ('work');
+
+CREATE TABLE shortcuts (
+    collection  TEXT NOT NULL REFERENCES collections (name),
+    alias       TEXT NOT NULL,
+    url         TEXT NOT NULL,
+    description TEXT,
+    owner       TEXT,
+    created_at  TEXT,
+    updated_at  TEXT,
+    settings    TEXT NOT NULL DEFAULT '{}', -- Every other field, as JSON
+    PRIMARY KEY (collection, alias)
+);
+
+CREATE TABLE shortcut_tags (
+    collection TEXT NOT NULL,
+    alias      TEXT NOT NULL,
+    tag        TEXT NOT NULL,
+    PRIMARY KEY (collection, alias, tag),
+    FOREIGN KEY (collection, alias) REFERENCES shortcuts (collection, alias) ON DELETE CASCADE
+);
+
+CREATE TABLE patterns (
+    position INTEGER PRIMARY KEY,
+    pattern  TEXT NOT NULL,
+    kind     TEXT NOT NULL,
+    url      TEXT NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE themes (
+    name  TEXT PRIMARY KEY,
+    theme TEXT NOT NULL -- The whole theme, as JSON
+);
+
+CREATE TABLE current_theme (
+    id    INTEGER PRIMARY KEY CHECK (id = 1),
+    theme TEXT NOT NULL
+);
+
+CREATE TABLE clicks (
+    id         INTEGER PRIMARY KEY,
+    collection TEXT NOT NULL REFERENCES collections (name),
+    alias      TEXT NOT NULL,
+    clicked_at TEXT NOT NULL
+);
+CREATE INDEX clicks_by_alias ON clicks (collection, alias);
+";
+
+// Fields stored in their own columns (or, for tags, their own table)
+const COLUMN_FIELDS: [&str; 6] = ["url", "description", "owner", "created_at", "updated_at", "tags"];
+
+type DbResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+pub struct SqliteStorage {
+    conn: Mutex<Connection>,
+    path: String,
+}
+
+impl SqliteStorage {
+    /// Opens (or creates) the database at `path`. A new database is filled
+    /// from whatever `import` holds before anything else can use it.
+    pub fn open(path: &str, import: &dyn Storage) -> io::Result<Self> {
+        let storage = Self::connect(path).map_err(|e| io::Error::other(format!("{path}: {e}")))?;
+        storage.with(|conn| migrate(conn, path, import))?;
+        Ok(storage)
+    }
+
+    fn connect(path: &str) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        Ok(SqliteStorage { conn: Mutex::new(conn), path: path.to_string() })
+    }
+
+    // Any failure here (a bad row, a locked or damaged database) is reported
+    // as an error other than NotFound, so whatever was being loaded stays
+    // read-only instead of being saved over with an empty stand-in
+    fn with<T>(&self, f: impl FnOnce(&mut Connection) -> DbResult<T>) -> io::Result<T> {
+        let mut conn = self.conn.lock().unwrap();
+        f(&mut conn).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", self.path, e)))
+    }
+}
+
+fn not_saved(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("no {what} saved yet"))
+}
+
+// Creates the tables, and on a brand new database copies in the JSON files
+// in the same transaction, so a failed import leaves nothing half done.
+fn migrate(conn: &mut Connection, path: &str, import: &dyn Storage) -> DbResult<()> {
+    let version: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version == SCHEMA_VERSION {
+        return Ok(());
+    }
+    if version > SCHEMA_VERSION {
+        return Err(format!("{path} was written by a newer version (schema {version})").into());
+    }
+
+    let tx = conn.transaction()?;
+    tx.execute_batch(SCHEMA)?;
+
+    // A missing file just means there was nothing to import
+    fn found<T>(loaded: io::Result<T>) -> DbResult<Option<T>> {
+        match loaded {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("can't import existing data: {e}").into()),
+        }
+    }
+    for collection in Collection::ALL {
+        if let Some(shortcuts) = found(import.load_shortcuts(collection))? {
+            write_shortcuts(&tx, collection, &shortcuts)?;
+            eprintln!("{path}: imported {} shortcuts into the {} collection", shortcuts.len(), collection.name());
+        }
+    }
+    if let Some(patterns) = found(import.load_patterns())? {
+        write_patterns(&tx, &patterns)?;
+        eprintln!("{path}: imported {} pattern shortcuts", patterns.len());
+    }
+    if let Some(themes) = found(import.load_themes())? {
+        write_themes(&tx, &themes)?;
+    }
+    if let Some(theme) = found(import.load_current_theme())? {
+        write_current_theme(&tx, &theme)?;
+    }
+
+    tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    tx.commit()?;
+    Ok(())
+}
+
+// Replaces every row of `collection`; tags go with their shortcut
+fn write_shortcuts(conn: &Connection, collection: Collection, shortcuts: &HashMap<String, Shortcut>) -> DbResult<()> {
+    let name = collection.name();
+    conn.execute("DELETE FROM shortcuts WHERE collection = ?1", [name])?;
+
+    let mut insert = conn.prepare(
+        "INSERT INTO shortcuts (collection, alias, url, description, owner, created_at, updated_at, settings)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+    let mut insert_tag = conn.prepare("INSERT OR IGNORE INTO shortcut_tags (collection, alias, tag) VALUES (?1, ?2, ?3)")?;
+
+    for (alias, shortcut) in shortcuts {
+        // A shortcut with only a URL serializes to a bare string; it has no other settings
+        let mut settings = match serde_json::to_value(shortcut)? {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        };
+        for field in COLUMN_FIELDS {
+            settings.remove(field);
+        }
+
+        insert.execute(params![
+            name,
+            alias,
+            shortcut.url,
+            shortcut.description,
+            shortcut.owner,
+            shortcut.created_at.map(|t| t.to_rfc3339()),
+            shortcut.updated_at.map(|t| t.to_rfc3339()),
+            Value::Object(settings).to_string(),
+        ])?;
+        for tag in &shortcut.tags {
+            insert_tag.execute([name, alias, tag])?;
+        }
+    }
+    Ok(())
+}
+
+fn read_shortcuts(conn: &Connection, collection: Collection) -> DbResult<HashMap<String, Shortcut>> {
+    let name = collection.name();
+
+    let mut tags: HashMap<String, Vec<Value>> = HashMap::new();
+    let mut tag_rows = conn.prepare("SELECT alias, tag FROM shortcut_tags WHERE collection = ?1 ORDER BY rowid")?;
+    for row in tag_rows.query_map([name], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))? {
+        let (alias, tag) = row?;
+        tags.entry(alias).or_default().push(Value::String(tag));
+    }
+
+    let mut rows = conn.prepare(
+        "SELECT alias, url, description, owner, created_at, updated_at, settings
+         FROM shortcuts WHERE collection = ?1",
+    )?;
+    let mut shortcuts = HashMap::new();
+    for row in rows.query_map([name], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            [row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?],
+            row.get::<_, String>(6)?,
+        ))
+    })? {
+        let (alias, columns, settings): (String, [Option<String>; 5], String) = row?;
+        // Put the columns back next to the other settings and let serde rebuild the record
+        let mut record: Map<String, Value> = serde_json::from_str(&settings)?;
+        for (field, value) in COLUMN_FIELDS.iter().zip(columns) {
+            if let Some(value) = value {
+                record.insert(field.to_string(), Value::String(value));
+            }
+        }
+        if let Some(tags) = tags.remove(&alias) {
+            record.insert("tags".to_string(), Value::Array(tags));
+        }
+        let shortcut: Shortcut = serde_json::from_value(Value::Object(record))
+            .map_err(|e| format!("{} shortcut \"{alias}\": {e}", collection.name()))?;
+        shortcuts.insert(alias, shortcut);
+    }
+    Ok(shortcuts)
+}
+
+fn kind_name(kind: PatternKind) -> &'static str {
+    match kind {
+        PatternKind::Wildcard => "wildcard",
+        PatternKind::Regex => "regex",
+    }
+}
+
+fn write_patterns(conn: &Connection, patterns: &[CompiledPattern]) -> DbResult<()> {
+    conn.execute("DELETE FROM patterns", [])?;
+    let mut insert =
+        conn.prepare("INSERT INTO patterns (position, pattern, kind, url, priority) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+    for (position, pattern) in patterns.iter().map(|p| &p.shortcut).enumerate() {
+        insert.execute(params![position, pattern.pattern, kind_name(pattern.kind), pattern.url, pattern.priority])?;
+    }
+    Ok(())
+}
+
+fn write_themes(conn: &Connection, themes: &HashMap<String, Theme>) -> DbResult<()> {
+    conn.execute("DELETE FROM themes", [])?;
+    let mut insert = conn.prepare("INSERT INTO themes (name, theme) VALUES (?1, ?2)")?;
+    for (name, theme) in themes {
+        insert.execute([name, &serde_json::to_string(theme)?])?;
+    }
+    Ok(())
+}
+
+fn write_current_theme(conn: &Connection, theme: &Theme) -> DbResult<()> {
+    conn.execute(
+        "INSERT INTO current_theme (id, theme) VALUES (1, ?1) ON CONFLICT (id) DO UPDATE SET theme = excluded.theme",
+        [serde_json::to_string(theme)?],
+    )?;
+    Ok(())
+}
+
+impl Storage for SqliteStorage {
+    fn load_shortcuts(&self, collection: Collection) -> io::Result<HashMap<String, Shortcut>> {
+        self.with(|conn| read_shortcuts(conn, collection))
+    }
+
+    fn save_shortcuts(&self, collection: Collection, shortcuts: &HashMap<String, Shortcut>) -> io::Result<()> {
+        self.with(|conn| {
+            let tx = conn.transaction()?;
+            write_shortcuts(&tx, collection, shortcuts)?;
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    fn load_patterns(&self) -> io::Result<Vec<CompiledPattern>> {
+        self.with(|conn| {
+            let mut rows = conn.prepare("SELECT pattern, kind, url, priority FROM patterns ORDER BY position")?;
+            let mut patterns = Vec::new();
+            for row in rows.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get(2)?, row.get(3)?))
+            })? {
+                let (pattern, kind, url, priority) = row?;
+                let kind = match kind.as_str() {
+                    "wildcard" => PatternKind::Wildcard,
+                    "regex" => PatternKind::Regex,
+                    other => return Err(format!("pattern \"{pattern}\" has unknown kind \"{other}\"").into()),
+                };
+                patterns.push(CompiledPattern::new(PatternShortcut { pattern, kind, url, priority }));
+            }
+            sort_by_priority(&mut patterns);
+            Ok(patterns)
+        })
+    }
+
+    fn save_patterns(&self, patterns: &[CompiledPattern]) -> io::Result<()> {
+        self.with(|conn| {
+            let tx = conn.transaction()?;
+            write_patterns(&tx, patterns)?;
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    fn load_themes(&self) -> io::Result<HashMap<String, Theme>> {
+        let themes = self.with(|conn| {
+            let mut rows = conn.prepare("SELECT name, theme FROM themes")?;
+            let mut themes = HashMap::new();
+            for row in rows.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))? {
+                let (name, theme) = row?;
+                themes.insert(name, serde_json::from_str(&theme)?);
+            }
+            Ok(themes)
+        })?;
+        if themes.is_empty() {
+            return Err(not_saved("themes"));
+        }
+        Ok(themes)
+    }
+
+    fn save_themes(&self, themes: &HashMap<String, Theme>) -> io::Result<()> {
+        self.with(|conn| {
+            let tx = conn.transaction()?;
+            write_themes(&tx, themes)?;
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    fn load_current_theme(&self) -> io::Result<Theme> {
+        let theme = self.with(|conn| {
+            let theme: Option<String> =
+                conn.query_row("SELECT theme FROM current_theme WHERE id = 1", [], |row| row.get(0)).optional()?;
+            Ok(theme.map(|t| serde_json::from_str(&t)).transpose()?)
+        })?;
+        theme.ok_or_else(|| not_saved("current theme"))
+    }
+
+    fn save_current_theme(&self, theme: &Theme) -> io::Result<()> {
+        self.with(|conn| write_current_theme(conn, theme))
+    }
+
+    fn records_clicks(&self) -> bool {
+        true
+    }
+
+    fn record_click(&self, collection: Collection, alias: &str, at: DateTime<Utc>) -> io::Result<()> {
+        self.with(|conn| {
+            conn.execute(
+                "INSERT INTO clicks (collection, alias, clicked_at) VALUES (?1, ?2, ?3)",
+                params![collection.name(), alias, at.to_rfc3339()],
+            )?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::elements::theme::default_dark_theme;
+    use crate::storage::MemoryStorage;
+
+    // A collection from `{"alias": <shortcut as in shortcuts.json>, ...}`
+    fn collection(json: &str) -> HashMap<String, Shortcut> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    // Shortcuts as JSON, to compare them whole
+    fn as_json(shortcuts: &HashMap<String, Shortcut>) -> Value {
+        serde_json::to_value(shortcuts).unwrap()
+    }
+
+    fn settings_of(storage: &SqliteStorage, alias: &str) -> Map<String, Value> {
+        let settings: String = storage
+            .with(|conn| Ok(conn.query_row("SELECT settings FROM shortcuts WHERE alias = ?1", [alias], |row| row.get(0))?))
+            .unwrap();
+        serde_json::from_str(&settings).unwrap()
+    }
+
+    #[test]
+    fn shortcuts_round_trip_through_columns_and_settings() {
+        let storage = SqliteStorage::open(":memory:", &MemoryStorage::default()).unwrap();
+        let saved = collection(
+            r#"{
+                "jira": {
+                    "url": "https://jira.example.com/browse/{*}",
+                    "redirect_status": 301,
+                    "cache_max_age": 600,
+                    "claims_host": true,
+                    "description": "Tickets",
+                    "owner": "platform",
+                    "tags": ["work"],
+                    "created_at": "2026-01-02T03:04:05Z",
+                    "updated_at": "2026-02-03T04:05:06Z",
+                    "destinations": {"mode": "failover", "alternatives": ["https://jira-backup.example.com"]}
+                },
+                "wiki": "https://wiki.example.com"
+            }"#,
+        );
+        storage.save_shortcuts(Collection::Work, &saved).unwrap();
+        assert_eq!(as_json(&storage.load_shortcuts(Collection::Work).unwrap()), as_json(&saved));
+
+        // Columns hold their own fields; settings hold everything else
+        let settings = settings_of(&storage, "jira");
+        assert!(COLUMN_FIELDS.iter().all(|field| !settings.contains_key(*field)));
+        assert_eq!(settings["redirect_status"], 301);
+        assert_eq!(settings["destinations"]["mode"], "failover");
+    }
+
+    #[test]
+    fn tags_keep_their_order() {
+        let storage = SqliteStorage::open(":memory:", &MemoryStorage::default()).unwrap();
+        let saved = collection(r#"{"wiki": {"url": "https://wiki.example.com", "tags": ["zeta", "alpha", "mid"]}}"#);
+        // Saving again replaces the rows, tags included
+        storage.save_shortcuts(Collection::Shortcuts, &saved).unwrap();
+        storage.save_shortcuts(Collection::Shortcuts, &saved).unwrap();
+        assert_eq!(storage.load_shortcuts(Collection::Shortcuts).unwrap()["wiki"].tags, ["zeta", "alpha", "mid"]);
+    }
+
+    #[test]
+    fn url_only_shortcut_comes_back_as_a_bare_url() {
+        let storage = SqliteStorage::open(":memory:", &MemoryStorage::default()).unwrap();
+        storage.save_shortcuts(Collection::Hidden, &collection(r#"{"gh": "https://github.com"}"#)).unwrap();
+
+        assert!(settings_of(&storage, "gh").is_empty());
+        let loaded = storage.load_shortcuts(Collection::Hidden).unwrap();
+        assert_eq!(serde_json::to_string(&loaded["gh"]).unwrap(), r#""https://github.com""#);
+    }
+
+    #[test]
+    fn new_database_imports_existing_data_once() {
+        let json = MemoryStorage::default();
+        json.save_shortcuts(Collection::Work, &collection(r#"{"jira": "https://jira.example.com"}"#)).unwrap();
+        let pattern = PatternShortcut {
+            pattern: "pr-*".to_string(),
+            kind: PatternKind::Wildcard,
+            url: "https://github.com/org/repo/pull/${1}".to_string(),
+            priority: 0,
+        };
+        json.save_patterns(&[CompiledPattern::new(pattern)]).unwrap();
+        json.save_current_theme(&default_dark_theme()).unwrap();
+
+        let storage = SqliteStorage::open(":memory:", &json).unwrap();
+        let work = storage.load_shortcuts(Collection::Work).unwrap();
+        assert_eq!(work.keys().collect::<Vec<_>>(), ["jira"]);
+        assert_eq!(storage.load_patterns().unwrap()[0].shortcut.pattern, "pr-*");
+        assert_eq!(storage.load_current_theme().unwrap().name, default_dark_theme().name);
+        assert!(storage.load_themes().is_err_and(|e| e.kind() == io::ErrorKind::NotFound));
+
+        // Once the schema is in place, later opens leave the data alone
+        let later = MemoryStorage::default();
+        later.save_shortcuts(Collection::Work, &collection(r#"{"wiki": "https://wiki.example.com"}"#)).unwrap();
+        storage.with(|conn| migrate(conn, ":memory:", &later)).unwrap();
+        assert!(storage.load_shortcuts(Collection::Work).unwrap().contains_key("jira"));
+        assert!(!storage.load_shortcuts(Collection::Work).unwrap().contains_key("wiki"));
+
+        // A database from a newer version is refused rather than guessed at
+        storage.with(|conn| Ok(conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1)?)).unwrap();
+        assert!(storage.with(|conn| migrate(conn, ":memory:", &later)).is_err());
+    }
+
+    #[test]
+    fn clicks_are_recorded() {
+        let storage = SqliteStorage::open(":memory:", &MemoryStorage::default()).unwrap();
+        assert!(storage.records_clicks() && !MemoryStorage::default().records_clicks());
+
+        storage.record_click(Collection::Work, "jira", Utc::now()).unwrap();
+        let clicks: i64 = storage
+            .with(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM clicks WHERE alias = 'jira'", [], |row| row.get(0))?))
+            .unwrap();
+        assert_eq!(clicks, 1);
+    }
+}
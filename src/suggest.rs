@@ -0,0 +1,162 @@
+// Ranks saved aliases by how closely they resemble an alias that wasn't found.
+
+use std::collections::HashMap;
+
+use crate::app_state::Shortcut;
+
+// How a candidate alias relates to what was typed, best first
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum MatchKind {
+    CaseOnly,  // Same letters, different case
+    Prefix,    // One is the start of the other
+    Typo,      // Within a few single-character edits
+    Substring, // One appears somewhere inside the other
+}
+
+pub struct Suggestion {
+    pub alias: String,
+    pub url: String,
+    pub kind: MatchKind,
+    pub distance: usize,
+}
+
+impl Suggestion {
+    /// A match close enough that it's almost certainly what was meant.
+    pub fn is_strong(&self) -> bool {
+        match self.kind {
+            MatchKind::CaseOnly => true,
+            MatchKind::Typo => self.distance <= 1 || (self.distance == 2 && self.alias.chars().count() >= 6),
+            _ => false,
+        }
+    }
+}
+
+/// Levenshtein distance counted in characters rather than bytes.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+fn classify(typed: &str, alias: &str) -> Option<(MatchKind, usize)> {
+    let typed = typed.to_lowercase();
+    let candidate = alias.to_lowercase();
+    let distance = edit_distance(&typed, &candidate);
+
+    // Allow roughly one edit per three characters, but always at least one
+    let max_typo = (typed.chars().count() / 3).max(1);
+
+    let kind = if typed == candidate {
+        MatchKind::CaseOnly
+    } else if candidate.starts_with(&typed) || typed.starts_with(&candidate) {
+        MatchKind::Prefix
+    } else if distance <= max_typo {
+        MatchKind::Typo
+    } else if candidate.contains(&typed) || typed.contains(&candidate) {
+        MatchKind::Substring
+    } else {
+        return None;
+    };
+
+    Some((kind, distance))
+}
+
+/// Returns up to `limit` aliases from `collections` that resemble `typed`,
+/// best match first. An alias present in several collections is listed once.
+pub fn suggest(typed: &str, collections: &[&HashMap<String, Shortcut>], limit: usize) -> Vec<Suggestion> {
+    if typed.is_empty() {
+        return Vec::new();
+    }
+
+    let mut found: Vec<Suggestion> = Vec::new();
+    for collection in collections {
+        for (alias, shortcut) in collection.iter() {
+            if found.iter().any(|s| &s.alias == alias) {
+                continue;
+            }
+            if let Some((kind, distance)) = classify(typed, alias) {
+                found.push(Suggestion {
+                    alias: alias.clone(),
+                    url: shortcut.url.clone(),
+                    kind,
+                    distance,
+                });
+            }
+        }
+    }
+
+    found.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then(a.distance.cmp(&b.distance))
+            .then_with(|| a.alias.cmp(&b.alias))
+    });
+    found.truncate(limit);
+    found
+}
+
+/// The single suggestion worth offering as a one-click redirect, if there is
+/// exactly one strong match.
+pub fn only_strong_match(suggestions: &[Suggestion]) -> Option<&Suggestion> {
+    let mut strong = suggestions.iter().filter(|s| s.is_strong());
+    match (strong.next(), strong.next()) {
+        (Some(only), None) => Some(only),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A collection from `{"alias": "url", ...}` JSON
+    fn collection(json: &str) -> HashMap<String, Shortcut> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn aliases(suggestions: &[Suggestion]) -> Vec<&str> {
+        suggestions.iter().map(|s| s.alias.as_str()).collect()
+    }
+
+    #[test]
+    fn suggestions_rank_by_kind_then_distance() {
+        let shortcuts = collection(
+            r#"{"github": "https://github.com", "git": "https://git-scm.com", "mygithbx": "https://x", "gitlab": "https://gitlab.com"}"#,
+        );
+        assert_eq!(aliases(&suggest("githb", &[&shortcuts], 10)), ["git", "github", "mygithbx"]);
+        assert_eq!(aliases(&suggest("githb", &[&shortcuts], 2)), ["git", "github"]);
+        assert!(suggest("", &[&shortcuts], 10).is_empty());
+    }
+
+    #[test]
+    fn alias_in_several_collections_is_suggested_once() {
+        let shortcuts = collection(r#"{"wiki": "https://wiki.example.com"}"#);
+        let work = collection(r#"{"wiki": "https://wiki.corp.example.com"}"#);
+        let found = suggest("wikk", &[&shortcuts, &work], 10);
+        assert_eq!(aliases(&found), ["wiki"]);
+        assert_eq!(found[0].url, "https://wiki.example.com");
+    }
+
+    #[test]
+    fn only_strong_match_needs_exactly_one() {
+        let shortcuts = collection(r#"{"github": "https://github.com", "git": "https://git-scm.com"}"#);
+        let found = suggest("githb", &[&shortcuts], 10);
+        assert_eq!(only_strong_match(&found).map(|s| s.alias.as_str()), Some("github"));
+
+        let shortcuts = collection(r#"{"docs": "https://a", "dogs": "https://b"}"#);
+        assert!(only_strong_match(&suggest("dots", &[&shortcuts], 10)).is_none());
+
+        let shortcuts = collection(r#"{"documentation": "https://a"}"#);
+        assert!(only_strong_match(&suggest("doc", &[&shortcuts], 10)).is_none());
+    }
+}